@@ -0,0 +1,184 @@
+use crate::common::{self, *};
+use crate::events;
+
+use objc::runtime::{self, Imp};
+use objc::Encode;
+
+const KEY_PATH: &str = "effectiveAppearance";
+const NS_APPEARANCE_NAME_AQUA: &str = "NSAppearanceNameAqua";
+const NS_APPEARANCE_NAME_DARK_AQUA: &str = "NSAppearanceNameDarkAqua";
+
+/// Light or dark system appearance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+pub type OnAppearanceChanged = Box<dyn FnMut(&mut dyn controls::Member, Appearance)>;
+
+/// Light / dark appearance of windows and controls of this backend.
+///
+/// Members not backed by a view or window, like trays, follow the application appearance and cannot force their own.
+pub trait HasAppearance {
+    /// Appearance the member is actually drawn with, either forced or inherited from the window and application.
+    fn appearance(&self) -> Appearance;
+    /// Forces the appearance of the member and everything inside it, `None` to inherit it again.
+    fn set_appearance(&mut self, appearance: Option<Appearance>);
+    fn forced_appearance(&self) -> Option<Appearance>;
+    /// Called whenever the effective appearance changes, e.g. when the user switches the system to dark mode.
+    fn on_appearance_changed(&mut self, callback: Option<OnAppearanceChanged>);
+}
+
+impl<T: controls::Member + ?Sized> HasAppearance for T {
+    fn appearance(&self) -> Appearance {
+        unsafe {
+            let id = self.native_id() as cocoa_id;
+            if common::is_view_or_window(id) {
+                from_native(msg_send![id, effectiveAppearance])
+            } else {
+                let app: cocoa_id = msg_send![class!(NSApplication), sharedApplication];
+                from_native(msg_send![app, effectiveAppearance])
+            }
+        }
+    }
+    fn set_appearance(&mut self, appearance: Option<Appearance>) {
+        unsafe {
+            let id = self.native_id() as cocoa_id;
+            if common::is_view_or_window(id) {
+                let () = msg_send![id, setAppearance: to_native(appearance)];
+            }
+        }
+    }
+    fn forced_appearance(&self) -> Option<Appearance> {
+        unsafe {
+            let id = self.native_id() as cocoa_id;
+            if !common::is_view_or_window(id) {
+                return None;
+            }
+            let appearance: cocoa_id = msg_send![id, appearance];
+            if appearance.is_null() {
+                None
+            } else {
+                Some(from_native(appearance))
+            }
+        }
+    }
+    fn on_appearance_changed(&mut self, callback: Option<OnAppearanceChanged>) {
+        let id = self.native_id() as cocoa_id;
+        unsafe {
+            if !common::is_view_or_window(id) {
+                return;
+            }
+            if let Some(handlers) = events::handlers_or_default(id) {
+                let observe = callback.is_some();
+                handlers.on_appearance_changed = callback;
+                if observe && handlers.observed_appearance.is_none() {
+                    handlers.observed_appearance = Some(from_native(msg_send![id, effectiveAppearance]));
+                    add_observer(id, id);
+                } else if !observe {
+                    stop_observing(id);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) unsafe fn from_native(appearance: cocoa_id) -> Appearance {
+    if appearance.is_null() {
+        return Appearance::Light;
+    }
+    let names: cocoa_id = msg_send![class!(NSMutableArray), arrayWithCapacity: 2 as NSUInteger];
+    let aqua = NSString::alloc(nil).init_str(NS_APPEARANCE_NAME_AQUA);
+    let dark_aqua = NSString::alloc(nil).init_str(NS_APPEARANCE_NAME_DARK_AQUA);
+    let () = msg_send![names, addObject: aqua];
+    let () = msg_send![names, addObject: dark_aqua];
+    let best: cocoa_id = msg_send![appearance, bestMatchFromAppearancesWithNames: names];
+    let () = msg_send![aqua, release];
+    let () = msg_send![dark_aqua, release];
+    if common::nsstring_to_string(best) == NS_APPEARANCE_NAME_DARK_AQUA {
+        Appearance::Dark
+    } else {
+        Appearance::Light
+    }
+}
+
+pub(crate) unsafe fn to_native(appearance: Option<Appearance>) -> cocoa_id {
+    match appearance {
+        Some(appearance) => {
+            let name = NSString::alloc(nil).init_str(match appearance {
+                Appearance::Light => NS_APPEARANCE_NAME_AQUA,
+                Appearance::Dark => NS_APPEARANCE_NAME_DARK_AQUA,
+            });
+            let native: cocoa_id = msg_send![class!(NSAppearance), appearanceNamed: name];
+            let () = msg_send![name, release];
+            native
+        }
+        None => nil,
+    }
+}
+
+/// Subscribes `observer` to the effective appearance changes of `id` through KVO.
+pub(crate) unsafe fn add_observer(id: cocoa_id, observer: cocoa_id) {
+    let key = NSString::alloc(nil).init_str(KEY_PATH);
+    let () = msg_send![id, addObserver:observer forKeyPath:key options:0 as NSUInteger context:ptr::null_mut::<c_void>()];
+    let () = msg_send![key, release];
+}
+
+pub(crate) unsafe fn remove_observer(id: cocoa_id, observer: cocoa_id) {
+    let key = NSString::alloc(nil).init_str(KEY_PATH);
+    let () = msg_send![id, removeObserver:observer forKeyPath:key];
+    let () = msg_send![key, release];
+}
+
+pub(crate) unsafe fn is_appearance_key_path(key_path: cocoa_id) -> bool {
+    common::nsstring_to_string(key_path) == KEY_PATH
+}
+
+/// Stops observing the member appearance, must be called before its handlers are freed.
+pub(crate) unsafe fn stop_observing(id: cocoa_id) {
+    if let Some(handlers) = events::handlers(id) {
+        if handlers.observed_appearance.take().is_some() {
+            remove_observer(id, id);
+        }
+    }
+}
+
+pub(crate) unsafe fn install(class: *const Class) {
+    let class = class as *mut Class;
+    let types = ffi::CString::new(format!(
+        "{}{}{}{}{}{}{}",
+        <()>::encode().as_str(),
+        <*mut Object>::encode().as_str(),
+        <Sel>::encode().as_str(),
+        <cocoa_id>::encode().as_str(),
+        <cocoa_id>::encode().as_str(),
+        <cocoa_id>::encode().as_str(),
+        <*mut c_void>::encode().as_str()
+    ))
+    .unwrap();
+    let imp: Imp = mem::transmute(observe_value as extern "C" fn(&mut Object, Sel, cocoa_id, cocoa_id, cocoa_id, *mut c_void));
+    runtime::class_addMethod(class, sel!(observeValueForKeyPath:ofObject:change:context:), imp, types.as_ptr());
+}
+
+extern "C" fn observe_value(this: &mut Object, _: Sel, key_path: cocoa_id, object: cocoa_id, change: cocoa_id, context: *mut c_void) {
+    unsafe {
+        let id = this as *mut Object;
+        if object != id || !is_appearance_key_path(key_path) {
+            let () = msg_send![super(this, events::superclass(this)), observeValueForKeyPath:key_path ofObject:object change:change context:context];
+            return;
+        }
+        let appearance = from_native(msg_send![id, effectiveAppearance]);
+        if let Some(handlers) = events::handlers(id) {
+            if handlers.observed_appearance == Some(appearance) {
+                return;
+            }
+            handlers.observed_appearance = Some(appearance);
+            if let Some(ref mut callback) = handlers.on_appearance_changed {
+                if let Some(member) = events::member_from_cocoa_id_mut(id) {
+                    (callback.as_mut())(member, appearance);
+                }
+            }
+        }
+    }
+}
@@ -4,10 +4,18 @@ use super::*;
 use plygui_api::controls;
 use plygui_api::types;
 
+use crate::appearance::Appearance;
+use crate::clipboard::{Clipboard, CocoaClipboard};
+use crate::notifications::{CocoaNotifier, Notification, Notifier, OnNotification};
+use crate::runloop::{Mailbox, RunLoopIntegration, RunLoopStats};
+use crate::timers::{OnTimer, TimerHandle};
+
 use cocoa::appkit::{NSApplication, NSApplicationActivationPolicy};
 use dispatch::Queue;
 
 use std::any::TypeId;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 lazy_static! {
     static ref WINDOW_CLASS: RefClass = unsafe { register_window_class("PlyguiApplication", BASE_CLASS, |_| {}) };
@@ -15,6 +23,8 @@ lazy_static! {
 }
 const BASE_CLASS: &str = "NSApplication";
 const DEFAULT_FRAME_SLEEP_MS: u32 = 10;
+const NS_TERMINATE_CANCEL: NSUInteger = 0;
+const NS_TERMINATE_NOW: NSUInteger = 1;
 
 pub type Application = AApplication<CocoaApplication>;
 
@@ -22,7 +32,252 @@ pub struct CocoaApplication {
     app: cocoa_id,
     delegate: *mut Object,
     name: String,
-    sleep: u32,
+    pub(crate) sleep: u32,
+    notifier: Box<dyn Notifier>,
+    on_notification: Option<OnNotification>,
+    notification_counter: u64,
+    alive: Arc<AtomicBool>,
+    pub(crate) run_loop: Option<RunLoopIntegration>,
+    pub(crate) stats: RunLoopStats,
+    pub(crate) awake_since: Option<Instant>,
+    pub(crate) asleep_since: Option<Instant>,
+    termination_policy: TerminationPolicy,
+    activation_policy: ActivationPolicy,
+    on_launch: Option<OnLifecycle>,
+    on_activate: Option<OnLifecycle>,
+    on_deactivate: Option<OnLifecycle>,
+    on_reopen: Option<OnReopen>,
+    on_should_terminate: Option<OnShouldTerminate>,
+    /// Set once `exit` got the consent of `on_should_terminate`, so that AppKit does not ask again.
+    exiting: bool,
+    clipboard: Box<dyn Clipboard>,
+    on_appearance_changed: Option<OnApplicationAppearanceChanged>,
+    observed_appearance: Appearance,
+}
+
+/// When the application quits by itself, `controls::Application::exit` quits regardless of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationPolicy {
+    /// Once the last window or tray is closed.
+    LastRootClosed,
+    /// Once the last window is closed, even if trays are still there.
+    LastWindowClosed,
+    /// Keeps running without any windows or trays, e.g. to be reopened from the Dock.
+    Explicit,
+}
+
+impl Default for TerminationPolicy {
+    fn default() -> Self {
+        TerminationPolicy::LastRootClosed
+    }
+}
+
+/// Presence of the application in the Dock and the app switcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationPolicy {
+    /// Regular while there are windows, accessory with trays only.
+    Automatic,
+    /// Dock icon and menu bar.
+    Regular,
+    /// No Dock icon, windows can still be activated.
+    Accessory,
+    /// No Dock icon and no windows activation.
+    Prohibited,
+}
+
+impl Default for ActivationPolicy {
+    fn default() -> Self {
+        ActivationPolicy::Automatic
+    }
+}
+
+pub type OnLifecycle = Box<dyn FnMut(&mut dyn controls::Application)>;
+/// Receives whether the application has visible windows, returns `false` to suppress the default reopen handling.
+pub type OnReopen = Box<dyn FnMut(&mut dyn controls::Application, bool) -> bool>;
+/// Returns `false` to veto the termination, e.g. because of unsaved changes.
+pub type OnShouldTerminate = Box<dyn FnMut(&mut dyn controls::Application) -> bool>;
+pub type OnApplicationAppearanceChanged = Box<dyn FnMut(&mut dyn controls::Application, Appearance)>;
+
+/// Cloneable handle of the application that can be sent to other threads.
+///
+/// Closures posted through it run on the main thread as soon as the main run loop gets to them,
+/// and are silently dropped once the application itself is gone.
+#[derive(Clone)]
+pub struct ApplicationHandle {
+    app: usize,
+    alive: Arc<AtomicBool>,
+    mailbox: Arc<Mailbox>,
+}
+
+unsafe impl Send for ApplicationHandle {}
+unsafe impl Sync for ApplicationHandle {}
+
+impl ApplicationHandle {
+    /// Queues `f` onto the main thread and returns immediately.
+    pub fn post<F>(&self, f: F)
+    where
+        F: FnOnce(&mut dyn controls::Application) + Send + 'static,
+    {
+        if self.is_alive() {
+            // the application may be dropped before the main thread gets to `f`, so check again there
+            let alive = self.alive.clone();
+            self.mailbox.post(Box::new(move |app| {
+                if alive.load(Ordering::SeqCst) {
+                    f(app)
+                }
+            }));
+        }
+    }
+    /// Runs `f` on the main thread and waits for its result, `None` if the application is gone.
+    ///
+    /// Called from the main thread itself, `f` is executed in place.
+    pub fn invoke_sync<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut dyn controls::Application) -> R + Send,
+        R: Send,
+    {
+        let is_main: BOOL = unsafe { msg_send![class!(NSThread), isMainThread] };
+        if is_main == YES {
+            return unsafe { self.app_mut() }.map(f);
+        }
+        // `app_mut` checks `alive` on the main thread, where the application is dropped
+        Queue::main().exec_sync(|| unsafe { self.app_mut() }.map(f))
+    }
+    /// Whether the application this handle points to still exists.
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+    pub(crate) unsafe fn app_mut<'a>(&self) -> Option<&'a mut dyn controls::Application> {
+        self.app_impl_mut().map(|app| app as &mut dyn controls::Application)
+    }
+    unsafe fn app_impl_mut<'a>(&self) -> Option<&'a mut Application> {
+        if self.is_alive() {
+            Some(&mut *(self.app as *mut Application))
+        } else {
+            None
+        }
+    }
+}
+
+/// Cocoa-specific application functionality not covered by `controls::Application`.
+pub trait ApplicationExt {
+    /// Posts a local notification, returns its identifier as passed later to the `on_notification` callback.
+    fn post_notification(&mut self, notification: Notification) -> String;
+    /// Removes a delivered notification from the notification center.
+    fn remove_notification(&mut self, identifier: &str);
+    fn on_notification(&mut self, callback: Option<OnNotification>);
+    /// Replaces the notification delivery.
+    fn set_notifier(&mut self, notifier: Box<dyn Notifier>);
+    /// Thread-safe handle for dispatching work onto the UI thread.
+    fn handle(&self) -> ApplicationHandle;
+    /// Calls `callback` on the main run loop after `interval_ms`, and then every `interval_ms` if `repeating`.
+    /// Intervals are at least 1 ms.
+    fn start_timer(&mut self, interval_ms: u32, repeating: bool, callback: OnTimer) -> TimerHandle;
+    /// Main run loop counters accumulated since the start or the last `reset_run_loop_stats`.
+    fn run_loop_stats(&self) -> RunLoopStats;
+    fn reset_run_loop_stats(&mut self);
+    fn set_termination_policy(&mut self, policy: TerminationPolicy);
+    fn termination_policy(&self) -> TerminationPolicy;
+    fn set_activation_policy(&mut self, policy: ActivationPolicy);
+    fn activation_policy(&self) -> ActivationPolicy;
+    fn on_launch(&mut self, callback: Option<OnLifecycle>);
+    fn on_activate(&mut self, callback: Option<OnLifecycle>);
+    fn on_deactivate(&mut self, callback: Option<OnLifecycle>);
+    /// Called when the Dock icon is clicked or the application is launched again while running.
+    fn on_reopen(&mut self, callback: Option<OnReopen>);
+    /// Called for every termination request, including Cmd+Q, `TerminationPolicy` and `controls::Application::exit`.
+    /// A veto keeps everything running as it was, `exit` asks before closing any window or tray.
+    fn on_should_terminate(&mut self, callback: Option<OnShouldTerminate>);
+    /// The general pasteboard, unless replaced with `set_clipboard`.
+    fn clipboard(&mut self) -> &mut dyn Clipboard;
+    /// Replaces the clipboard.
+    fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>);
+    /// Appearance the application windows inherit unless they force their own.
+    fn appearance(&self) -> Appearance;
+    /// Forces the appearance of all windows, `None` to follow the system setting.
+    fn set_appearance(&mut self, appearance: Option<Appearance>);
+    fn on_appearance_changed(&mut self, callback: Option<OnApplicationAppearanceChanged>);
+}
+
+impl ApplicationExt for Application {
+    fn post_notification(&mut self, notification: Notification) -> String {
+        let app = self.inner_mut();
+        app.notification_counter += 1;
+        let identifier = format!("{}-{}", app.name, app.notification_counter);
+        app.notifier.post(&identifier, &notification);
+        identifier
+    }
+    fn remove_notification(&mut self, identifier: &str) {
+        self.inner_mut().notifier.remove(identifier);
+    }
+    fn on_notification(&mut self, callback: Option<OnNotification>) {
+        self.inner_mut().on_notification = callback;
+    }
+    fn set_notifier(&mut self, notifier: Box<dyn Notifier>) {
+        self.inner_mut().notifier = notifier;
+    }
+    fn handle(&self) -> ApplicationHandle {
+        let app = self.inner();
+        ApplicationHandle {
+            app: unsafe { cast_cocoa_id_to_ptr(app.app) }.unwrap() as usize,
+            alive: app.alive.clone(),
+            mailbox: app.run_loop.as_ref().unwrap().mailbox(),
+        }
+    }
+    fn start_timer(&mut self, interval_ms: u32, repeating: bool, callback: OnTimer) -> TimerHandle {
+        crate::timers::start(self.handle(), interval_ms, repeating, callback)
+    }
+    fn run_loop_stats(&self) -> RunLoopStats {
+        self.inner().stats
+    }
+    fn reset_run_loop_stats(&mut self) {
+        self.inner_mut().stats = RunLoopStats::default();
+    }
+    fn set_termination_policy(&mut self, policy: TerminationPolicy) {
+        self.inner_mut().termination_policy = policy;
+    }
+    fn termination_policy(&self) -> TerminationPolicy {
+        self.inner().termination_policy
+    }
+    fn set_activation_policy(&mut self, policy: ActivationPolicy) {
+        self.inner_mut().activation_policy = policy;
+        self.inner_mut().apply_execution_policy();
+    }
+    fn activation_policy(&self) -> ActivationPolicy {
+        self.inner().activation_policy
+    }
+    fn on_launch(&mut self, callback: Option<OnLifecycle>) {
+        self.inner_mut().on_launch = callback;
+    }
+    fn on_activate(&mut self, callback: Option<OnLifecycle>) {
+        self.inner_mut().on_activate = callback;
+    }
+    fn on_deactivate(&mut self, callback: Option<OnLifecycle>) {
+        self.inner_mut().on_deactivate = callback;
+    }
+    fn on_reopen(&mut self, callback: Option<OnReopen>) {
+        self.inner_mut().on_reopen = callback;
+    }
+    fn on_should_terminate(&mut self, callback: Option<OnShouldTerminate>) {
+        self.inner_mut().on_should_terminate = callback;
+    }
+    fn clipboard(&mut self) -> &mut dyn Clipboard {
+        self.inner_mut().clipboard.as_mut()
+    }
+    fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.inner_mut().clipboard = clipboard;
+    }
+    fn appearance(&self) -> Appearance {
+        unsafe { crate::appearance::from_native(msg_send![self.inner().app, effectiveAppearance]) }
+    }
+    fn set_appearance(&mut self, appearance: Option<Appearance>) {
+        unsafe {
+            let () = msg_send![self.inner().app, setAppearance: crate::appearance::to_native(appearance)];
+        }
+    }
+    fn on_appearance_changed(&mut self, callback: Option<OnApplicationAppearanceChanged>) {
+        self.inner_mut().on_appearance_changed = callback;
+    }
 }
 
 impl HasNativeIdInner for CocoaApplication {
@@ -46,41 +301,105 @@ impl CocoaApplication {
     fn apply_execution_policy(&mut self) {
         unsafe {
             let base = &mut cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap().base;
-            if base.windows.len() < 1 && base.trays.len() > 0 {
-                self.app.setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory);
-            } else {
-                self.app.setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular);
-            }
+            let policy = match self.activation_policy {
+                ActivationPolicy::Automatic => {
+                    if base.windows.len() < 1 && base.trays.len() > 0 {
+                        NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory
+                    } else {
+                        NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular
+                    }
+                }
+                ActivationPolicy::Regular => NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular,
+                ActivationPolicy::Accessory => NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory,
+                ActivationPolicy::Prohibited => NSApplicationActivationPolicy::NSApplicationActivationPolicyProhibited,
+            };
+            self.app.setActivationPolicy_(policy);
         }
     }
     pub fn maybe_exit(&mut self) -> bool {
+        let base = unsafe { &mut cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap().base };
+        let exit = match self.termination_policy {
+            TerminationPolicy::LastRootClosed => base.windows.len() < 1 && base.trays.len() < 1,
+            TerminationPolicy::LastWindowClosed => base.windows.len() < 1,
+            TerminationPolicy::Explicit => false,
+        };
+        if exit {
+            self.terminate();
+        }
+        exit
+    }
+    /// Closes the root window at `index`, together with its sheets, modal sessions and child windows.
+    fn close_window(&mut self, index: usize, skip_callbacks: bool) -> bool {
+        let app = unsafe { cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap() };
+        let window = app.base.windows[index].as_any_mut().downcast_mut::<crate::window::Window>().unwrap();
+        if !window.inner_mut().inner_mut().inner_mut().inner_mut().close(skip_callbacks) {
+            return false;
+        }
+        let mut window = app.base.windows.remove(index);
+        unsafe { window.as_any_mut().downcast_mut::<crate::window::Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().detach_chain(app) };
+        self.apply_execution_policy();
+        self.maybe_exit();
+        true
+    }
+    /// Runs `on_should_terminate`, `true` if the application may quit.
+    fn should_terminate(&mut self) -> bool {
+        if self.exiting {
+            return true;
+        }
+        let app = unsafe { cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap() };
+        let app2 = unsafe { cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap() };
+        match app.inner_mut().on_should_terminate {
+            Some(ref mut callback) => (callback.as_mut())(app2),
+            None => true,
+        }
+    }
+    /// Asks AppKit to quit, which goes through `on_should_terminate`.
+    fn terminate(&mut self) {
         unsafe {
-            let base = &mut cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap().base;
-            if base.windows.len() < 1 && base.trays.len() < 1 {
-                self.app.setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyProhibited);
-                let app: cocoa_id = msg_send![WINDOW_CLASS.0, sharedApplication];
-                let () = msg_send![app, terminate:self.app];
-                true
-            } else {
-                false
-            }
+            let app: cocoa_id = msg_send![WINDOW_CLASS.0, sharedApplication];
+            let () = msg_send![app, terminate:self.app];
         }
     }
 }
 
 impl<O: controls::Application> NewApplicationInner<O> for CocoaApplication {
     fn with_uninit_params(u: &mut mem::MaybeUninit<O>, name: &str) -> Self {
-        let a = CocoaApplication {
+        let mut a = CocoaApplication {
             app: unsafe { msg_send![WINDOW_CLASS.0, sharedApplication] },
             delegate: unsafe { msg_send!(DELEGATE.0, new) },
             name: name.to_owned(),
             sleep: DEFAULT_FRAME_SLEEP_MS,
+            notifier: Box::new(CocoaNotifier),
+            on_notification: None,
+            notification_counter: 0,
+            alive: Arc::new(AtomicBool::new(true)),
+            run_loop: None,
+            stats: RunLoopStats::default(),
+            awake_since: None,
+            asleep_since: None,
+            termination_policy: TerminationPolicy::default(),
+            activation_policy: ActivationPolicy::default(),
+            on_launch: None,
+            on_activate: None,
+            on_deactivate: None,
+            on_reopen: None,
+            on_should_terminate: None,
+            exiting: false,
+            clipboard: Box::new(CocoaClipboard::general()),
+            on_appearance_changed: None,
+            observed_appearance: Appearance::Light,
         };
         unsafe {
             let selfptr = u as *mut _ as *mut c_void;
             (&mut *a.app).set_ivar(IVAR, selfptr);
             (&mut *a.delegate).set_ivar(IVAR, selfptr);
             let () = msg_send![a.app, setDelegate: a.delegate];
+            let center = crate::notifications::default_center();
+            if !center.is_null() {
+                let () = msg_send![center, setDelegate: a.delegate];
+            }
+            a.observed_appearance = crate::appearance::from_native(msg_send![a.app, effectiveAppearance]);
+            crate::appearance::add_observer(a.app, a.delegate);
         }
         a
     }
@@ -92,12 +411,12 @@ impl ApplicationInner for CocoaApplication {
         let ab = AApplication::with_inner(
             <Self as NewApplicationInner<Application>>::with_uninit_params(b.as_mut(), name.as_ref()),
         );
-        let selfptr = b.as_ptr() as usize;
-        let a = unsafe {
+        let mut a = unsafe {
 	        b.as_mut_ptr().write(ab);
 	        b.assume_init()
         };
-        Queue::main().exec_async(move || application_frame_runner(selfptr));
+        let run_loop = RunLoopIntegration::install(a.as_mut());
+        a.inner_mut().run_loop = Some(run_loop);
         a
     }
     fn add_root(&mut self, m: Box<dyn controls::Closeable>) -> &mut dyn controls::Member {
@@ -126,15 +445,7 @@ impl ApplicationInner for CocoaApplication {
         let base = unsafe { &mut cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap().base }; 
         match arg {
             types::FindBy::Id(id) => {
-                (0..base.windows.len()).into_iter().find(|i| if base.windows[*i].id() == id 
-                    && base.windows[*i].as_any_mut().downcast_mut::<crate::window::Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().close(skip_callbacks) {
-                        base.windows.remove(*i);
-                        self.apply_execution_policy();
-                        self.maybe_exit();
-                        true
-                    } else {
-                        false
-                }).is_some()
+                (0..base.windows.len()).into_iter().find(|i| base.windows[*i].id() == id && self.close_window(*i, skip_callbacks)).is_some()
                 || 
                 (0..base.trays.len()).into_iter().find(|i| if base.trays[*i].id() == id 
                     && base.trays[*i].as_any_mut().downcast_mut::<crate::tray::Tray>().unwrap().inner_mut().close(skip_callbacks) {
@@ -147,15 +458,7 @@ impl ApplicationInner for CocoaApplication {
                 }).is_some()
             }
             types::FindBy::Tag(tag) => {
-                (0..base.windows.len()).into_iter().find(|i| if base.windows[*i].tag().is_some() && base.windows[*i].tag().unwrap() == Cow::Borrowed(tag.into()) 
-                    && base.windows[*i].as_any_mut().downcast_mut::<crate::window::Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().close(skip_callbacks) {
-                        base.windows.remove(*i);
-                        self.apply_execution_policy();
-                        self.maybe_exit();
-                        true
-                    } else {
-                        false
-                }).is_some()
+                (0..base.windows.len()).into_iter().find(|i| base.windows[*i].tag().is_some() && base.windows[*i].tag().unwrap() == Cow::Borrowed(tag.into()) && self.close_window(*i, skip_callbacks)).is_some()
                 || 
                 (0..base.trays.len()).into_iter().find(|i| if base.trays[*i].tag().is_some() && base.trays[*i].tag().unwrap() == Cow::Borrowed(tag.into()) 
                     && base.trays[*i].as_any_mut().downcast_mut::<crate::tray::Tray>().unwrap().inner_mut().close(skip_callbacks) {
@@ -172,6 +475,7 @@ impl ApplicationInner for CocoaApplication {
     fn name(&self) -> ::std::borrow::Cow<'_, str> {
         ::std::borrow::Cow::Borrowed(self.name.as_ref())
     }
+    /// Delay in milliseconds before the frame callbacks that asked to be called again are run.
     fn frame_sleep(&self) -> u32 {
         self.sleep
     }
@@ -265,6 +569,10 @@ impl ApplicationInner for CocoaApplication {
     }
     #[allow(unused_comparisons)] // WAT?
     fn exit(&mut self) {
+        if !self.should_terminate() {
+            return;
+        }
+        self.exiting = true;
         let base = unsafe { &mut cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)).unwrap().base }; 
         
         for mut window in base.windows.drain(..) {
@@ -274,7 +582,7 @@ impl ApplicationInner for CocoaApplication {
             tray.as_any_mut().downcast_mut::<crate::tray::Tray>().unwrap().inner_mut().close(true);
         }
 
-        self.maybe_exit();
+        self.terminate();
     }
     fn roots<'a>(&'a self) -> Box<dyn Iterator<Item = &'a (dyn controls::Member)> + 'a> {
         unsafe { cast_cocoa_id_to_ptr(self.app).map(|ptr| &mut *(ptr as *mut _ as *mut Application)) }.unwrap().roots()
@@ -286,7 +594,9 @@ impl ApplicationInner for CocoaApplication {
 
 impl Drop for CocoaApplication {
     fn drop(&mut self) {
+        self.alive.store(false, Ordering::SeqCst);
         unsafe {
+            crate::appearance::remove_observer(self.app, self.delegate);
             let () = msg_send![self.app, dealloc];
             let () = msg_send![self.delegate, dealloc];
         }
@@ -302,6 +612,25 @@ unsafe fn register_delegate() -> RefClass {
         application_should_terminate_after_last_window_closed as extern "C" fn(&Object, Sel, cocoa_id) -> BOOL,
     );
     decl.add_method(sel!(applicationDidFinishLaunching:), application_did_finish_launching as extern "C" fn(&Object, Sel, cocoa_id));
+    decl.add_method(sel!(applicationDidBecomeActive:), application_did_become_active as extern "C" fn(&Object, Sel, cocoa_id));
+    decl.add_method(sel!(applicationDidResignActive:), application_did_resign_active as extern "C" fn(&Object, Sel, cocoa_id));
+    decl.add_method(
+        sel!(applicationShouldHandleReopen:hasVisibleWindows:),
+        application_should_handle_reopen as extern "C" fn(&Object, Sel, cocoa_id, BOOL) -> BOOL,
+    );
+    decl.add_method(
+        sel!(observeValueForKeyPath:ofObject:change:context:),
+        application_observe_value as extern "C" fn(&Object, Sel, cocoa_id, cocoa_id, cocoa_id, *mut c_void),
+    );
+    decl.add_method(sel!(applicationShouldTerminate:), application_should_terminate as extern "C" fn(&Object, Sel, cocoa_id) -> NSUInteger);
+    decl.add_method(
+        sel!(userNotificationCenter:didActivateNotification:),
+        user_notification_center_did_activate as extern "C" fn(&Object, Sel, cocoa_id, cocoa_id),
+    );
+    decl.add_method(
+        sel!(userNotificationCenter:shouldPresentNotification:),
+        user_notification_center_should_present as extern "C" fn(&Object, Sel, cocoa_id, cocoa_id) -> BOOL,
+    );
     decl.add_ivar::<*mut c_void>(IVAR);
 
     RefClass(decl.register())
@@ -313,39 +642,88 @@ extern "C" fn application_did_finish_launching(this: &Object, _sel: Sel, _notifi
             let () = msg_send![app.inner_mut().app, activateIgnoringOtherApps: YES];
         }
         app.inner_mut().apply_execution_policy();
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_launch {
+            (callback.as_mut())(app2);
+        }
     }
 }
 
-extern "C" fn application_should_terminate_after_last_window_closed(_: &Object, _: Sel, _: cocoa_id) -> BOOL {
-    NO
+extern "C" fn application_did_become_active(this: &Object, _: Sel, _: cocoa_id) {
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_activate {
+            (callback.as_mut())(app2);
+        }
+    }
 }
 
-fn application_frame_runner(selfptr: usize) {
-    let app: &mut Application = unsafe { mem::transmute(selfptr) };
-    let mut frame_callbacks;
-    {
-        frame_callbacks = 0;
-        let b = &mut app.base;
-        while frame_callbacks < defaults::MAX_FRAME_CALLBACKS {
-            match b.queue().try_recv() {
-                Ok(mut cmd) => {
-                    let app2: &mut Application = unsafe { mem::transmute(selfptr) };
-                    if (cmd.as_mut())(app2) {
-                        let _ = b.sender().send(cmd);
-                    }
-                    frame_callbacks += 1;
-                }
-                Err(e) => match e {
-                    mpsc::TryRecvError::Empty => break,
-                    mpsc::TryRecvError::Disconnected => unreachable!(),
-                },
+extern "C" fn application_did_resign_active(this: &Object, _: Sel, _: cocoa_id) {
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_deactivate {
+            (callback.as_mut())(app2);
+        }
+    }
+}
+
+extern "C" fn application_should_handle_reopen(this: &Object, _: Sel, _: cocoa_id, has_visible_windows: BOOL) -> BOOL {
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_reopen {
+            if !(callback.as_mut())(app2, has_visible_windows == YES) {
+                return NO;
             }
         }
     }
-    unsafe {
-        let () = msg_send![class!(NSThread), sleepForTimeInterval:(1.0 as f64 / app.inner().sleep as f64)];
+    YES
+}
+
+extern "C" fn application_observe_value(this: &Object, _: Sel, key_path: cocoa_id, _: cocoa_id, _: cocoa_id, _: *mut c_void) {
+    if !unsafe { crate::appearance::is_appearance_key_path(key_path) } {
+        return;
+    }
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        let appearance = unsafe { crate::appearance::from_native(msg_send![app.inner().app, effectiveAppearance]) };
+        if app.inner().observed_appearance == appearance {
+            return;
+        }
+        app.inner_mut().observed_appearance = appearance;
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_appearance_changed {
+            (callback.as_mut())(app2, appearance);
+        }
     }
-    Queue::main().exec_async(move || application_frame_runner(selfptr));
+}
+
+extern "C" fn application_should_terminate(this: &Object, _: Sel, _: cocoa_id) -> NSUInteger {
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        if !app.inner_mut().should_terminate() {
+            return NS_TERMINATE_CANCEL;
+        }
+        unsafe { app.inner_mut().app.setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyProhibited) };
+    }
+    NS_TERMINATE_NOW
+}
+
+extern "C" fn user_notification_center_did_activate(this: &Object, _: Sel, _: cocoa_id, notification: cocoa_id) {
+    let (identifier, response) = unsafe { crate::notifications::response(notification) };
+    if let Some(app) = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) } {
+        let app2 = unsafe { from_cocoa_id_mut(this as *const _ as *mut Object) }.unwrap();
+        if let Some(ref mut callback) = app.inner_mut().on_notification {
+            (callback.as_mut())(app2, identifier.as_str(), response);
+        }
+    }
+}
+
+extern "C" fn user_notification_center_should_present(_: &Object, _: Sel, _: cocoa_id, _: cocoa_id) -> BOOL {
+    // Show the banners even while the application is active
+    YES
+}
+
+extern "C" fn application_should_terminate_after_last_window_closed(_: &Object, _: Sel, _: cocoa_id) -> BOOL {
+    // AppKit knows nothing of trays, `maybe_exit` applies the `TerminationPolicy` instead
+    NO
 }
 
 unsafe fn from_cocoa_id_mut<'a>(id: cocoa_id) -> Option<&'a mut Application> {
@@ -1,4 +1,5 @@
 use crate::common::{self, *};
+use crate::style::{self, HasTextStyle, TextStyle};
 
 use cocoa::appkit::NSBezelStyle;
 
@@ -25,6 +26,58 @@ pub struct CocoaButton {
     h_left_clicked: Option<callbacks::OnClick>,
     h_right_clicked: Option<callbacks::OnClick>,
     skip_callbacks: bool,
+    style: TextStyle,
+}
+
+impl CocoaButton {
+    /// Sends the style fields that differ from `old`, untouched ones keep what the button already has.
+    fn apply_style(&mut self, old: &TextStyle) {
+        unsafe {
+            let control = self.base.control;
+            if self.style.font != old.font {
+                let () = msg_send![control, setFont: self.style.font.to_native()];
+            }
+            if self.style.alignment != old.alignment {
+                let () = msg_send![control, setAlignment: self.style.alignment.to_native()];
+            }
+            if self.style.text_color.is_some() {
+                self.apply_title_color();
+            } else if old.text_color.is_some() {
+                // drops the attributed title
+                let title: cocoa_id = msg_send![control, title];
+                let () = msg_send![control, setTitle: title];
+            }
+            if self.style.background_color != old.background_color {
+                let cell: cocoa_id = msg_send![control, cell];
+                let background: cocoa_id = self.style.background_color.map(|color| color.to_native()).unwrap_or(nil);
+                let () = msg_send![cell, setBackgroundColor: background];
+            }
+        }
+    }
+    /// Buttons only take the text color from an attributed title, which has to follow the title, font and alignment.
+    unsafe fn apply_title_color(&mut self) {
+        if let Some(color) = self.style.text_color {
+            let control = self.base.control;
+            let title: cocoa_id = msg_send![control, title];
+            let attributes = style::attributes(msg_send![control, font], color.to_native(), Some(self.style.alignment));
+            let attributed: cocoa_id = msg_send![class!(NSAttributedString), alloc];
+            let attributed: cocoa_id = msg_send![attributed, initWithString:title attributes:attributes];
+            let () = msg_send![control, setAttributedTitle: attributed];
+            let () = msg_send![attributed, release];
+        }
+    }
+}
+
+impl HasTextStyle for Button {
+    fn text_style(&self) -> &TextStyle {
+        &self.inner().inner().inner().style
+    }
+    fn set_text_style(&mut self, style: TextStyle) {
+        let button = self.inner_mut().inner_mut().inner_mut();
+        let old = mem::replace(&mut button.style, style);
+        button.apply_style(&old);
+        button.base.invalidate();
+    }
 }
 
 impl<O: controls::Button> NewButtonInner<O> for CocoaButton {
@@ -34,6 +87,7 @@ impl<O: controls::Button> NewButtonInner<O> for CocoaButton {
             h_left_clicked: None,
             h_right_clicked: None,
             skip_callbacks: false,
+            style: TextStyle::default(),
         };
         let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
         unsafe {
@@ -76,6 +130,7 @@ impl HasLabelInner for CocoaButton {
             let title = NSString::alloc(cocoa::base::nil).init_str(&label);
             let () = msg_send![self.base.control, setTitle: title];
             let () = msg_send![title, release];
+            self.apply_title_color();
         }
     }
 }
@@ -168,7 +223,7 @@ impl Drawable for CocoaButton {
                     layout::Size::MatchParent => parent_width as i32,
                     layout::Size::Exact(w) => w as i32,
                     layout::Size::WrapContent => {
-                        label_size = common::measure_nsstring(msg_send![self.base.control, title]);
+                        label_size = common::measure_nsstring(msg_send![self.base.control, title], msg_send![self.base.control, font], None);
                         label_size.0 as i32 + DEFAULT_PADDING + DEFAULT_PADDING
                     }
                 };
@@ -177,7 +232,7 @@ impl Drawable for CocoaButton {
                     layout::Size::Exact(h) => h as i32,
                     layout::Size::WrapContent => {
                         if label_size.1 < 1 {
-                            label_size = common::measure_nsstring(msg_send![self.base.control, title]);
+                            label_size = common::measure_nsstring(msg_send![self.base.control, title], msg_send![self.base.control, font], None);
                         }
                         label_size.1 as i32 + DEFAULT_PADDING + DEFAULT_PADDING
                     }
@@ -199,6 +254,9 @@ impl Spawnable for CocoaButton {
 
 extern "C" fn button_left_click(this: &mut Object, _: Sel, param: cocoa_id) {
     unsafe {
+        if crate::events::handle_mouse_down(this, param) {
+            return;
+        }
         let button = common::member_from_cocoa_id_mut::<Button>(this).unwrap();
         let () = msg_send![super(button.inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), mouseDown: param];
         if !button.inner().inner().inner().skip_callbacks {
@@ -212,6 +270,9 @@ extern "C" fn button_left_click(this: &mut Object, _: Sel, param: cocoa_id) {
 extern "C" fn button_right_click(this: &mut Object, _: Sel, param: cocoa_id) {
     //println!("right!");
     unsafe {
+        if crate::events::handle_mouse_down(this, param) {
+            return;
+        }
         let button = common::member_from_cocoa_id_mut::<Button>(this).unwrap();
         if let Some(ref mut cb) = button.inner_mut().inner_mut().inner_mut().h_right_clicked {
             let b2 = common::member_from_cocoa_id_mut::<Button>(this).unwrap();
@@ -0,0 +1,559 @@
+use crate::common::{self, *};
+use crate::style::{Color, Font};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiCanvas", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(drawRect:), draw_rect as extern "C" fn(&mut Object, Sel, NSRect));
+            decl.add_method(sel!(isFlipped), is_flipped as extern "C" fn(&mut Object, Sel) -> BOOL);
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSView";
+const NS_COMPOSITING_OPERATION_SOURCE_OVER: NSUInteger = 2;
+
+pub type Canvas = AMember<AControl<CocoaCanvas>>;
+
+/// Draws the canvas content, receives the canvas size in points.
+pub type OnDraw = Box<dyn FnMut(&mut dyn controls::Member, &mut dyn DrawContext, (f64, f64))>;
+
+/// 2D drawing with a top-left origin. Paths are built with `begin_path` .. `close_path` and painted with `fill` / `stroke`,
+/// which keep the path so it can be both filled and stroked.
+pub trait DrawContext {
+    fn save(&mut self);
+    fn restore(&mut self);
+
+    fn translate(&mut self, x: f64, y: f64);
+    fn scale(&mut self, x: f64, y: f64);
+    /// Rotates clockwise, in radians.
+    fn rotate(&mut self, angle: f64);
+
+    fn set_fill_color(&mut self, color: Color);
+    fn set_stroke_color(&mut self, color: Color);
+    fn set_line_width(&mut self, width: f64);
+
+    fn begin_path(&mut self);
+    fn move_to(&mut self, x: f64, y: f64);
+    fn line_to(&mut self, x: f64, y: f64);
+    fn curve_to(&mut self, cx1: f64, cy1: f64, cx2: f64, cy2: f64, x: f64, y: f64);
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+    fn ellipse(&mut self, x: f64, y: f64, width: f64, height: f64);
+    fn close_path(&mut self);
+    fn fill(&mut self);
+    fn stroke(&mut self);
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.begin_path();
+        self.rect(x, y, width, height);
+        self.fill();
+    }
+    /// Draws `text` in the fill color with its top-left corner at `x`, `y`.
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, font: &Font);
+    fn draw_image(&mut self, image: &image::DynamicImage, x: f64, y: f64, width: f64, height: f64);
+}
+
+/// A single `DrawContext` call, see `RecordingContext`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Save,
+    Restore,
+    Translate(f64, f64),
+    Scale(f64, f64),
+    Rotate(f64),
+    SetFillColor(Color),
+    SetStrokeColor(Color),
+    SetLineWidth(f64),
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    CurveTo(f64, f64, f64, f64, f64, f64),
+    Rect(f64, f64, f64, f64),
+    Ellipse(f64, f64, f64, f64),
+    ClosePath,
+    Fill,
+    Stroke,
+    Text { text: String, x: f64, y: f64, font: Font },
+    /// Images are recorded by their pixel size.
+    Image { pixels: (u32, u32), x: f64, y: f64, width: f64, height: f64 },
+}
+
+/// Context recording the calls instead of drawing, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingContext {
+    commands: Vec<DrawCommand>,
+}
+
+impl RecordingContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+    pub fn into_commands(self) -> Vec<DrawCommand> {
+        self.commands
+    }
+}
+
+impl DrawContext for RecordingContext {
+    fn save(&mut self) {
+        self.commands.push(DrawCommand::Save);
+    }
+    fn restore(&mut self) {
+        self.commands.push(DrawCommand::Restore);
+    }
+    fn translate(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::Translate(x, y));
+    }
+    fn scale(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::Scale(x, y));
+    }
+    fn rotate(&mut self, angle: f64) {
+        self.commands.push(DrawCommand::Rotate(angle));
+    }
+    fn set_fill_color(&mut self, color: Color) {
+        self.commands.push(DrawCommand::SetFillColor(color));
+    }
+    fn set_stroke_color(&mut self, color: Color) {
+        self.commands.push(DrawCommand::SetStrokeColor(color));
+    }
+    fn set_line_width(&mut self, width: f64) {
+        self.commands.push(DrawCommand::SetLineWidth(width));
+    }
+    fn begin_path(&mut self) {
+        self.commands.push(DrawCommand::BeginPath);
+    }
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::MoveTo(x, y));
+    }
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::LineTo(x, y));
+    }
+    fn curve_to(&mut self, cx1: f64, cy1: f64, cx2: f64, cy2: f64, x: f64, y: f64) {
+        self.commands.push(DrawCommand::CurveTo(cx1, cy1, cx2, cy2, x, y));
+    }
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.commands.push(DrawCommand::Rect(x, y, width, height));
+    }
+    fn ellipse(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.commands.push(DrawCommand::Ellipse(x, y, width, height));
+    }
+    fn close_path(&mut self) {
+        self.commands.push(DrawCommand::ClosePath);
+    }
+    fn fill(&mut self) {
+        self.commands.push(DrawCommand::Fill);
+    }
+    fn stroke(&mut self) {
+        self.commands.push(DrawCommand::Stroke);
+    }
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, font: &Font) {
+        self.commands.push(DrawCommand::Text { text: text.to_owned(), x, y, font: font.clone() });
+    }
+    fn draw_image(&mut self, image: &image::DynamicImage, x: f64, y: f64, width: f64, height: f64) {
+        use image::GenericImageView;
+
+        self.commands.push(DrawCommand::Image { pixels: image.dimensions(), x, y, width, height });
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PaintState {
+    fill: Color,
+    stroke: Color,
+    line_width: f64,
+}
+
+/// Draws into the current `NSGraphicsContext` of a flipped view.
+struct CocoaDrawContext {
+    path: cocoa_id,
+    state: PaintState,
+    saved: Vec<PaintState>,
+}
+
+impl CocoaDrawContext {
+    unsafe fn new() -> Self {
+        CocoaDrawContext {
+            path: msg_send![class!(NSBezierPath), new],
+            state: PaintState {
+                fill: Color::rgb(0, 0, 0),
+                stroke: Color::rgb(0, 0, 0),
+                line_width: 1.0,
+            },
+            saved: Vec::new(),
+        }
+    }
+}
+
+impl Drop for CocoaDrawContext {
+    fn drop(&mut self) {
+        // unbalanced `save` calls would corrupt the drawing of the views after the canvas
+        for _ in self.saved.drain(..) {
+            unsafe {
+                let () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+            }
+        }
+        unsafe {
+            let () = msg_send![self.path, release];
+        }
+    }
+}
+
+impl DrawContext for CocoaDrawContext {
+    fn save(&mut self) {
+        self.saved.push(self.state);
+        unsafe {
+            let () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+        }
+    }
+    fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+            unsafe {
+                let () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+            }
+        }
+    }
+    fn translate(&mut self, x: f64, y: f64) {
+        unsafe {
+            let transform: cocoa_id = msg_send![class!(NSAffineTransform), transform];
+            let () = msg_send![transform, translateXBy:x yBy:y];
+            let () = msg_send![transform, concat];
+        }
+    }
+    fn scale(&mut self, x: f64, y: f64) {
+        unsafe {
+            let transform: cocoa_id = msg_send![class!(NSAffineTransform), transform];
+            let () = msg_send![transform, scaleXBy:x yBy:y];
+            let () = msg_send![transform, concat];
+        }
+    }
+    fn rotate(&mut self, angle: f64) {
+        unsafe {
+            let transform: cocoa_id = msg_send![class!(NSAffineTransform), transform];
+            // clockwise on screen, as the y axis points down
+            let () = msg_send![transform, rotateByRadians: angle];
+            let () = msg_send![transform, concat];
+        }
+    }
+    fn set_fill_color(&mut self, color: Color) {
+        self.state.fill = color;
+    }
+    fn set_stroke_color(&mut self, color: Color) {
+        self.state.stroke = color;
+    }
+    fn set_line_width(&mut self, width: f64) {
+        self.state.line_width = width;
+    }
+    fn begin_path(&mut self) {
+        unsafe {
+            let () = msg_send![self.path, removeAllPoints];
+        }
+    }
+    fn move_to(&mut self, x: f64, y: f64) {
+        unsafe {
+            let () = msg_send![self.path, moveToPoint: NSPoint::new(x, y)];
+        }
+    }
+    fn line_to(&mut self, x: f64, y: f64) {
+        unsafe {
+            let () = msg_send![self.path, lineToPoint: NSPoint::new(x, y)];
+        }
+    }
+    fn curve_to(&mut self, cx1: f64, cy1: f64, cx2: f64, cy2: f64, x: f64, y: f64) {
+        unsafe {
+            let () = msg_send![self.path, curveToPoint:NSPoint::new(x, y) controlPoint1:NSPoint::new(cx1, cy1) controlPoint2:NSPoint::new(cx2, cy2)];
+        }
+    }
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        unsafe {
+            let () = msg_send![self.path, appendBezierPathWithRect: NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))];
+        }
+    }
+    fn ellipse(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        unsafe {
+            let () = msg_send![self.path, appendBezierPathWithOvalInRect: NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))];
+        }
+    }
+    fn close_path(&mut self) {
+        unsafe {
+            let () = msg_send![self.path, closePath];
+        }
+    }
+    fn fill(&mut self) {
+        unsafe {
+            let () = msg_send![self.state.fill.to_native(), setFill];
+            let () = msg_send![self.path, fill];
+        }
+    }
+    fn stroke(&mut self) {
+        unsafe {
+            let () = msg_send![self.state.stroke.to_native(), setStroke];
+            let () = msg_send![self.path, setLineWidth: self.state.line_width];
+            let () = msg_send![self.path, stroke];
+        }
+    }
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, font: &Font) {
+        unsafe {
+            let text = NSString::alloc(nil).init_str(text);
+            let attributes = crate::style::attributes(font.to_native(), self.state.fill.to_native(), None);
+            let () = msg_send![text, drawAtPoint:NSPoint::new(x, y) withAttributes:attributes];
+            let () = msg_send![text, release];
+        }
+    }
+    fn draw_image(&mut self, image: &image::DynamicImage, x: f64, y: f64, width: f64, height: f64) {
+        unsafe {
+            let img = common::image_to_native(image);
+            let zero = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0));
+            let () = msg_send![img, drawInRect:NSRect::new(NSPoint::new(x, y), NSSize::new(width, height)) fromRect:zero operation:NS_COMPOSITING_OPERATION_SOURCE_OVER fraction:1.0f64 respectFlipped:YES hints:nil];
+            let () = msg_send![img, release];
+        }
+    }
+}
+
+#[repr(C)]
+pub struct CocoaCanvas {
+    base: common::CocoaControlBase<Canvas>,
+    on_draw: Option<OnDraw>,
+}
+
+/// Custom drawn control. Its content is redrawn on `Control::invalidate` and whenever the system asks for it.
+pub trait CanvasExt {
+    fn on_draw(&mut self, callback: Option<OnDraw>);
+    /// Runs the draw callback against a `RecordingContext` with the current size and returns the recorded calls.
+    fn record(&mut self) -> Vec<DrawCommand>;
+}
+
+impl CanvasExt for Canvas {
+    fn on_draw(&mut self, callback: Option<OnDraw>) {
+        let canvas = self.inner_mut().inner_mut();
+        canvas.on_draw = callback;
+        unsafe {
+            let () = msg_send![canvas.base.control, setNeedsDisplay: YES];
+        }
+    }
+    fn record(&mut self) -> Vec<DrawCommand> {
+        let canvas = unsafe { common::member_from_cocoa_id_mut::<Canvas>(self.inner().inner().base.control) }.unwrap();
+        let canvas2 = unsafe { common::member_from_cocoa_id_mut::<Canvas>(self.inner().inner().base.control) }.unwrap();
+        let inner = canvas.inner_mut().inner_mut();
+        let frame = inner.base.frame();
+        let mut context = RecordingContext::new();
+        if let Some(ref mut callback) = inner.on_draw {
+            (callback.as_mut())(canvas2, &mut context, (frame.size.width, frame.size.height));
+        }
+        context.into_commands()
+    }
+}
+
+impl CocoaCanvas {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<Canvas>) -> Self {
+        let c = CocoaCanvas {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            on_draw: None,
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *c.base.control).set_ivar(common::IVAR, selfptr);
+        }
+        c
+    }
+    pub fn with_on_draw(callback: OnDraw) -> Box<Canvas> {
+        let mut b: Box<mem::MaybeUninit<Canvas>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                Self::with_uninit(b.as_mut()),
+            ),
+        );
+        let mut ab = unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        };
+        ab.inner_mut().inner_mut().on_draw = Some(callback);
+        ab
+    }
+}
+
+impl ControlInner for CocoaCanvas {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _x: i32, _y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        self.base.invalidate();
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, Canvas, ["Canvas"]);
+    }
+}
+
+impl HasNativeIdInner for CocoaCanvas {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaCanvas {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaCanvas {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaCanvas {}
+
+impl HasLayoutInner for CocoaCanvas {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drawable for CocoaCanvas {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                // there is no content size to wrap
+                let w = match control.layout.width {
+                    layout::Size::MatchParent | layout::Size::WrapContent => parent_width,
+                    layout::Size::Exact(w) => w,
+                };
+                let h = match control.layout.height {
+                    layout::Size::MatchParent | layout::Size::WrapContent => parent_height,
+                    layout::Size::Exact(h) => h,
+                };
+                (w, h)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        unsafe {
+            let () = msg_send![self.base.control, setNeedsDisplay: YES];
+        }
+        self.base.invalidate();
+    }
+}
+
+impl Spawnable for CocoaCanvas {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_on_draw(Box::new(|_, _, _| {}))
+    }
+}
+
+extern "C" fn draw_rect(this: &mut Object, _: Sel, _: NSRect) {
+    let canvas = unsafe { common::member_from_cocoa_id_mut::<Canvas>(this) }.unwrap();
+    let canvas2 = unsafe { common::member_from_cocoa_id_mut::<Canvas>(this) }.unwrap();
+    let inner = canvas.inner_mut().inner_mut();
+    let frame = inner.base.frame();
+    if let Some(ref mut callback) = inner.on_draw {
+        unsafe {
+            // transforms must not leak out of the canvas
+            let () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+            {
+                let mut context = CocoaDrawContext::new();
+                (callback.as_mut())(canvas2, &mut context, (frame.size.width, frame.size.height));
+            }
+            let () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+        }
+    }
+}
+extern "C" fn is_flipped(_: &mut Object, _: Sel) -> BOOL {
+    YES
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<Canvas>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<Canvas>(this).unwrap();
+        (b.inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut Canvas, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<Canvas>(param.width as u16, param.height as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_context_records_calls_in_order() {
+        let mut context = RecordingContext::new();
+        context.save();
+        context.set_fill_color(Color::rgb(1, 2, 3));
+        context.fill_rect(1.0, 2.0, 3.0, 4.0);
+        context.draw_text("text", 5.0, 6.0, &Font::default());
+        context.draw_image(&image::DynamicImage::new_rgba8(7, 8), 0.0, 0.0, 3.5, 4.0);
+        context.restore();
+
+        assert_eq!(
+            context.into_commands(),
+            vec![
+                DrawCommand::Save,
+                DrawCommand::SetFillColor(Color::rgb(1, 2, 3)),
+                DrawCommand::BeginPath,
+                DrawCommand::Rect(1.0, 2.0, 3.0, 4.0),
+                DrawCommand::Fill,
+                DrawCommand::Text { text: "text".to_owned(), x: 5.0, y: 6.0, font: Font::default() },
+                DrawCommand::Image { pixels: (7, 8), x: 0.0, y: 0.0, width: 3.5, height: 4.0 },
+                DrawCommand::Restore,
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_callback_paints_the_view_from_the_top_left() {
+        let canvas = CocoaCanvas::with_on_draw(Box::new(|_, context, (width, height)| {
+            context.set_fill_color(Color::rgb(255, 0, 0));
+            context.fill_rect(0.0, 0.0, width, height / 2.0);
+        }));
+        let control = canvas.inner().inner().base.control;
+        unsafe {
+            let () = msg_send![super(control, Class::get(BASE_CLASS).unwrap()), setFrameSize: NSSize::new(10.0, 10.0)];
+            let bounds: NSRect = msg_send![control, bounds];
+            let rep: cocoa_id = msg_send![control, bitmapImageRepForCachingDisplayInRect: bounds];
+            let () = msg_send![control, cacheDisplayInRect:bounds toBitmapImageRep:rep];
+            let pixel = |y: NSInteger| -> (f64, f64) {
+                let color: cocoa_id = msg_send![rep, colorAtX:5 as NSInteger y:y];
+                (msg_send![color, redComponent], msg_send![color, alphaComponent])
+            };
+            let (red, alpha) = pixel(1);
+            assert!(red > 0.9 && alpha > 0.9);
+            let (_, alpha) = pixel(8);
+            assert!(alpha < 0.1);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::common::{self, *};
+
+use std::path::{Path, PathBuf};
+
+const NS_PNG_FILE_TYPE: NSUInteger = 4;
+
+/// Access to the system pasteboard, see `ApplicationExt::clipboard`.
+///
+/// Every `set_*` replaces the whole clipboard content and increments the change count.
+pub trait Clipboard {
+    fn text(&self) -> Option<String>;
+    fn set_text(&mut self, text: &str) -> bool;
+    fn html(&self) -> Option<String>;
+    fn set_html(&mut self, html: &str) -> bool;
+    fn image(&self) -> Option<image::DynamicImage>;
+    fn set_image(&mut self, image: &image::DynamicImage) -> bool;
+    fn file_urls(&self) -> Vec<PathBuf>;
+    fn set_file_urls(&mut self, paths: &[&Path]) -> bool;
+    fn clear(&mut self);
+    /// Changes whenever the clipboard content changes, also by other applications. Poll it to detect updates.
+    fn change_count(&self) -> isize;
+}
+
+/// The general `NSPasteboard`.
+pub struct CocoaClipboard {
+    pasteboard: cocoa_id,
+}
+
+impl CocoaClipboard {
+    pub fn general() -> Self {
+        CocoaClipboard {
+            pasteboard: unsafe { msg_send![class!(NSPasteboard), generalPasteboard] },
+        }
+    }
+    unsafe fn string_for_type(&self, kind: &str) -> Option<String> {
+        let kind = NSString::alloc(nil).init_str(kind);
+        let string: cocoa_id = msg_send![self.pasteboard, stringForType: kind];
+        let () = msg_send![kind, release];
+        if string.is_null() {
+            None
+        } else {
+            Some(common::nsstring_to_string(string))
+        }
+    }
+    unsafe fn set_string_for_type(&mut self, value: &str, kind: &str) -> bool {
+        let kind = NSString::alloc(nil).init_str(kind);
+        let value = NSString::alloc(nil).init_str(value);
+        let _: NSInteger = msg_send![self.pasteboard, clearContents];
+        let written: BOOL = msg_send![self.pasteboard, setString:value forType:kind];
+        let () = msg_send![value, release];
+        let () = msg_send![kind, release];
+        written == YES
+    }
+    unsafe fn write_objects(&mut self, objects: cocoa_id) -> bool {
+        let _: NSInteger = msg_send![self.pasteboard, clearContents];
+        let written: BOOL = msg_send![self.pasteboard, writeObjects: objects];
+        written == YES
+    }
+}
+
+impl Clipboard for CocoaClipboard {
+    fn text(&self) -> Option<String> {
+        unsafe { self.string_for_type("public.utf8-plain-text") }
+    }
+    fn set_text(&mut self, text: &str) -> bool {
+        unsafe { self.set_string_for_type(text, "public.utf8-plain-text") }
+    }
+    fn html(&self) -> Option<String> {
+        unsafe { self.string_for_type("public.html") }
+    }
+    fn set_html(&mut self, html: &str) -> bool {
+        unsafe { self.set_string_for_type(html, "public.html") }
+    }
+    fn image(&self) -> Option<image::DynamicImage> {
+        unsafe {
+            let img: cocoa_id = msg_send![class!(NSImage), alloc];
+            let img: cocoa_id = msg_send![img, initWithPasteboard: self.pasteboard];
+            if img.is_null() {
+                return None;
+            }
+            let tiff: cocoa_id = msg_send![img, TIFFRepresentation];
+            let rep: cocoa_id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+            let png: cocoa_id = if rep.is_null() { nil } else { msg_send![rep, representationUsingType:NS_PNG_FILE_TYPE properties:nil] };
+            let () = msg_send![img, release];
+            if png.is_null() {
+                return None;
+            }
+            let bytes: *const u8 = msg_send![png, bytes];
+            let len: NSUInteger = msg_send![png, length];
+            image::load_from_memory(slice::from_raw_parts(bytes, len as usize)).ok()
+        }
+    }
+    fn set_image(&mut self, image: &image::DynamicImage) -> bool {
+        unsafe {
+            let img = common::image_to_native(image);
+            let objects: cocoa_id = msg_send![class!(NSArray), arrayWithObject: img];
+            let written = self.write_objects(objects);
+            let () = msg_send![img, release];
+            written
+        }
+    }
+    fn file_urls(&self) -> Vec<PathBuf> {
+        unsafe {
+            let classes: cocoa_id = msg_send![class!(NSArray), arrayWithObject: class!(NSURL)];
+            let key = NSString::alloc(nil).init_str("NSPasteboardURLReadingFileURLsOnlyKey");
+            let yes: cocoa_id = msg_send![class!(NSNumber), numberWithBool: YES];
+            let options: cocoa_id = msg_send![class!(NSDictionary), dictionaryWithObject:yes forKey:key];
+            let urls: cocoa_id = msg_send![self.pasteboard, readObjectsForClasses:classes options:options];
+            let () = msg_send![key, release];
+            if urls.is_null() {
+                return Vec::new();
+            }
+            let count: NSUInteger = msg_send![urls, count];
+            (0..count)
+                .map(|i| {
+                    let url: cocoa_id = msg_send![urls, objectAtIndex: i];
+                    PathBuf::from(common::nsstring_to_string(msg_send![url, path]))
+                })
+                .collect()
+        }
+    }
+    fn set_file_urls(&mut self, paths: &[&Path]) -> bool {
+        unsafe {
+            let objects: cocoa_id = msg_send![class!(NSMutableArray), arrayWithCapacity: paths.len() as NSUInteger];
+            for path in paths {
+                let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+                let url: cocoa_id = msg_send![class!(NSURL), fileURLWithPath: path];
+                let () = msg_send![objects, addObject: url];
+                let () = msg_send![path, release];
+            }
+            self.write_objects(objects)
+        }
+    }
+    fn clear(&mut self) {
+        unsafe {
+            let _: NSInteger = msg_send![self.pasteboard, clearContents];
+        }
+    }
+    fn change_count(&self) -> isize {
+        unsafe { msg_send![self.pasteboard, changeCount] }
+    }
+}
+
+#[derive(Clone)]
+enum Content {
+    Empty,
+    Text(String),
+    Html(String),
+    Image(image::DynamicImage),
+    FileUrls(Vec<PathBuf>),
+}
+
+/// Clipboard kept in memory, for tests.
+#[derive(Clone)]
+pub struct MemoryClipboard {
+    content: Content,
+    change_count: isize,
+}
+
+impl Default for MemoryClipboard {
+    fn default() -> Self {
+        MemoryClipboard {
+            content: Content::Empty,
+            change_count: 0,
+        }
+    }
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn replace(&mut self, content: Content) -> bool {
+        self.content = content;
+        self.change_count += 1;
+        true
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn text(&self) -> Option<String> {
+        match self.content {
+            Content::Text(ref text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+    fn set_text(&mut self, text: &str) -> bool {
+        self.replace(Content::Text(text.to_owned()))
+    }
+    fn html(&self) -> Option<String> {
+        match self.content {
+            Content::Html(ref html) => Some(html.clone()),
+            _ => None,
+        }
+    }
+    fn set_html(&mut self, html: &str) -> bool {
+        self.replace(Content::Html(html.to_owned()))
+    }
+    fn image(&self) -> Option<image::DynamicImage> {
+        match self.content {
+            Content::Image(ref image) => Some(image.clone()),
+            _ => None,
+        }
+    }
+    fn set_image(&mut self, image: &image::DynamicImage) -> bool {
+        self.replace(Content::Image(image.clone()))
+    }
+    fn file_urls(&self) -> Vec<PathBuf> {
+        match self.content {
+            Content::FileUrls(ref paths) => paths.clone(),
+            _ => Vec::new(),
+        }
+    }
+    fn set_file_urls(&mut self, paths: &[&Path]) -> bool {
+        self.replace(Content::FileUrls(paths.iter().map(|path| path.to_path_buf()).collect()))
+    }
+    fn clear(&mut self) {
+        self.replace(Content::Empty);
+    }
+    fn change_count(&self) -> isize {
+        self.change_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    #[test]
+    fn memory_clipboard_replaces_content() {
+        let mut clipboard = MemoryClipboard::new();
+        assert_eq!(clipboard.text(), None);
+        assert_eq!(clipboard.change_count(), 0);
+
+        assert!(clipboard.set_text("text"));
+        assert_eq!(clipboard.text(), Some("text".to_owned()));
+        assert_eq!(clipboard.html(), None);
+
+        assert!(clipboard.set_html("<b>html</b>"));
+        assert_eq!(clipboard.text(), None);
+        assert_eq!(clipboard.html(), Some("<b>html</b>".to_owned()));
+
+        assert!(clipboard.set_file_urls(&[Path::new("/tmp/a"), Path::new("/tmp/b")]));
+        assert_eq!(clipboard.file_urls(), vec![PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]);
+        assert_eq!(clipboard.html(), None);
+
+        assert!(clipboard.set_image(&image::DynamicImage::new_rgba8(2, 3)));
+        assert_eq!(clipboard.image().map(|image| (image.width(), image.height())), Some((2, 3)));
+        assert!(clipboard.file_urls().is_empty());
+
+        clipboard.clear();
+        assert!(clipboard.image().is_none());
+        assert_eq!(clipboard.change_count(), 5);
+    }
+
+    #[test]
+    fn pasteboard_round_trips_content() {
+        // a private pasteboard keeps the test off the user's clipboard
+        let mut clipboard = CocoaClipboard {
+            pasteboard: unsafe { msg_send![class!(NSPasteboard), pasteboardWithUniqueName] },
+        };
+        let start = clipboard.change_count();
+
+        assert!(clipboard.set_text("text"));
+        assert_eq!(clipboard.text(), Some("text".to_owned()));
+        assert!(clipboard.change_count() > start);
+
+        assert!(clipboard.set_html("<b>html</b>"));
+        assert_eq!(clipboard.html(), Some("<b>html</b>".to_owned()));
+        assert_eq!(clipboard.text(), None);
+
+        assert!(clipboard.set_file_urls(&[Path::new("/tmp")]));
+        assert_eq!(clipboard.file_urls(), vec![PathBuf::from("/tmp")]);
+
+        assert!(clipboard.set_image(&image::DynamicImage::new_rgba8(2, 3)));
+        assert_eq!(clipboard.image().map(|image| (image.width(), image.height())), Some((2, 3)));
+
+        clipboard.clear();
+        assert_eq!(clipboard.text(), None);
+        assert!(clipboard.image().is_none());
+        unsafe {
+            let () = msg_send![clipboard.pasteboard, releaseGlobally];
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::common::{self, *};
+use crate::dropdown::{self, OnSelect};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiComboBox", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(numberOfItemsInComboBox:), datasource_len as extern "C" fn(&mut Object, Sel, cocoa_id) -> NSInteger);
+            decl.add_method(sel!(comboBox:objectValueForItemAtIndex:), item_value as extern "C" fn(&mut Object, Sel, cocoa_id, NSInteger) -> cocoa_id);
+            decl.add_method(sel!(comboBoxSelectionDidChange:), selection_changed as extern "C" fn(&mut Object, Sel, cocoa_id));
+            decl.add_method(sel!(controlTextDidChange:), text_changed as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSComboBox";
+
+pub type ComboBox = AMember<AControl<AContainer<AAdapted<CocoaComboBox>>>>;
+
+/// Editable text field with a dropdown of the adapter items, whose titles are taken from the item views text.
+#[repr(C)]
+pub struct CocoaComboBox {
+    base: common::CocoaControlBase<ComboBox>,
+    items: Vec<Box<dyn controls::Control>>,
+    on_select: Option<OnSelect>,
+}
+
+pub trait ComboBoxExt {
+    /// `None` if nothing is picked or the user edited the text since.
+    fn selected(&self) -> Option<usize>;
+    /// Also puts the item title into the text field. Does not call the `on_select` callback.
+    fn select(&mut self, index: Option<usize>);
+    fn on_select(&mut self, callback: Option<OnSelect>);
+    fn set_text(&mut self, text: &str);
+    fn text(&self) -> String;
+}
+
+impl ComboBoxExt for ComboBox {
+    fn selected(&self) -> Option<usize> {
+        let index: NSInteger = unsafe { msg_send![self.inner().inner().inner().inner().base.control, indexOfSelectedItem] };
+        if index < 0 { None } else { Some(index as usize) }
+    }
+    fn select(&mut self, index: Option<usize>) {
+        let combo = self.inner_mut().inner_mut().inner_mut().inner_mut();
+        unsafe {
+            match index.filter(|index| *index < combo.items.len()) {
+                Some(index) => {
+                    let () = msg_send![combo.base.control, selectItemAtIndex: index as NSInteger];
+                    let () = msg_send![combo.base.control, setObjectValue: dropdown::item_title(combo.items[index].native_id() as cocoa_id)];
+                }
+                None => {
+                    let selected: NSInteger = msg_send![combo.base.control, indexOfSelectedItem];
+                    if selected < 0 {
+                        return;
+                    }
+                    let () = msg_send![combo.base.control, deselectItemAtIndex: selected];
+                }
+            }
+        }
+    }
+    fn on_select(&mut self, callback: Option<OnSelect>) {
+        self.inner_mut().inner_mut().inner_mut().inner_mut().on_select = callback;
+    }
+    fn set_text(&mut self, text: &str) {
+        unsafe {
+            let text = NSString::alloc(nil).init_str(text);
+            let () = msg_send![self.inner().inner().inner().inner().base.control, setStringValue: text];
+            let () = msg_send![text, release];
+        }
+    }
+    fn text(&self) -> String {
+        unsafe {
+            let text: cocoa_id = msg_send![self.inner().inner().inner().inner().base.control, stringValue];
+            common::nsstring_to_string(text)
+        }
+    }
+}
+
+impl CocoaComboBox {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<ComboBox>) -> Self {
+        let c = CocoaComboBox {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            items: Vec::new(),
+            on_select: None,
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *c.base.control).set_ivar(common::IVAR, selfptr);
+            let () = msg_send![c.base.control, setUsesDataSource: YES];
+            let () = msg_send![c.base.control, setCompletes: YES];
+        }
+        c
+    }
+    pub fn with_adapter(adapter: Box<dyn types::Adapter>) -> Box<ComboBox> {
+        let mut b: Box<mem::MaybeUninit<ComboBox>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                AContainer::with_inner(
+                    AAdapted::with_inner(
+                        Self::with_uninit(b.as_mut()),
+                        adapter,
+                        &mut b,
+                    ),
+                )
+            ),
+        );
+        unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        }
+    }
+    fn add_item_inner(&mut self, base: &mut MemberBase, indexes: &[usize]) {
+        let i = indexes[0];
+        let (member, control, adapter, _) = unsafe { ComboBox::adapter_base_parts_mut(base) };
+        let (pw, ph) = control.measured;
+        let this: &mut ComboBox = unsafe { utils::base_to_impl_mut(member) };
+
+        let mut item = adapter.adapter.spawn_item_view(indexes, this).unwrap();
+        item.on_added_to_container(this, 0, 0, utils::coord_to_size(pw as i32) as u16, utils::coord_to_size(ph as i32) as u16);
+
+        self.items.insert(i, item);
+    }
+    fn remove_item_inner(&mut self, base: &mut MemberBase, indexes: &[usize]) {
+        let this: &mut ComboBox = unsafe { utils::base_to_impl_mut(base) };
+        self.items.remove(indexes[0]).on_removed_from_container(this);
+    }
+}
+
+impl AdaptedInner for CocoaComboBox {
+    fn on_item_change(&mut self, base: &mut MemberBase, value: adapter::Change) {
+        match value {
+            adapter::Change::Added(at, _) => {
+                self.add_item_inner(base, at);
+            },
+            adapter::Change::Removed(at) => {
+                self.remove_item_inner(base, at);
+            },
+            adapter::Change::Edited(at, _) => {
+                self.remove_item_inner(base, at);
+                self.add_item_inner(base, at);
+            },
+        }
+        unsafe {
+            let () = msg_send![self.base.control, reloadData];
+        }
+        self.base.invalidate();
+    }
+}
+
+impl ContainerInner for CocoaComboBox {
+    fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
+        for child in self.items.as_mut_slice() {
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member_mut().id() == *id {
+                        return Some(child.as_mut());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container_mut() {
+                let ret = c.find_control_mut(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+    fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
+        for child in self.items.as_slice() {
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member().id() == *id {
+                        return Some(child.as_ref());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container() {
+                let ret = c.find_control(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+}
+
+impl ControlInner for CocoaComboBox {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, x: i32, y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        control.coords = Some((x, y));
+
+        let (member, _, adapter, _) = unsafe { ComboBox::adapter_base_parts_mut(member) };
+
+        adapter.adapter.for_each(&mut (|indexes, _node| {
+            self.add_item_inner(member, indexes);
+        }));
+        unsafe {
+            let () = msg_send![self.base.control, setDelegate: self.base.control];
+            let () = msg_send![self.base.control, setDataSource: self.base.control];
+        }
+        self.base.invalidate();
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        unsafe {
+            let () = msg_send![self.base.control, setDelegate: nil];
+            let () = msg_send![self.base.control, setDataSource: nil];
+        }
+        let this: &ComboBox = unsafe { common::member_from_cocoa_id(self.base.control).unwrap() };
+        for mut child in self.items.drain(..) {
+            child.on_removed_from_container(this);
+        }
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, ComboBox, ["ComboBox"]);
+    }
+}
+
+impl HasLayoutInner for CocoaComboBox {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl HasNativeIdInner for CocoaComboBox {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaComboBox {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaComboBox {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaComboBox {}
+
+impl Drawable for CocoaComboBox {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                let fitting: NSSize = unsafe { msg_send![self.base.control, fittingSize] };
+                let w = match control.layout.width {
+                    layout::Size::MatchParent => parent_width as i32,
+                    layout::Size::Exact(w) => w as i32,
+                    layout::Size::WrapContent => cmp::max(fitting.width as i32, defaults::THE_ULTIMATE_ANSWER_TO_EVERYTHING as i32),
+                };
+                let h = match control.layout.height {
+                    layout::Size::MatchParent => parent_height as i32,
+                    layout::Size::Exact(h) => h as i32,
+                    layout::Size::WrapContent => fitting.height as i32,
+                };
+                (cmp::max(0, w) as u16, cmp::max(0, h) as u16)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drop for CocoaComboBox {
+    fn drop(&mut self) {
+        let this: &ComboBox = unsafe { common::member_from_cocoa_id(self.base.control).unwrap() };
+        for ref mut child in self.items.as_mut_slice() {
+            child.on_removed_from_container(this);
+        }
+    }
+}
+
+impl Spawnable for CocoaComboBox {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_adapter(Box::new(types::imp::StringVecAdapter::<crate::imp::Text>::new()))
+    }
+}
+
+extern "C" fn datasource_len(this: &mut Object, _: Sel, _: cocoa_id) -> NSInteger {
+    let combo = unsafe { common::member_from_cocoa_id::<ComboBox>(this).unwrap() };
+    combo.inner().inner().inner().inner().items.len() as NSInteger
+}
+extern "C" fn item_value(this: &mut Object, _: Sel, _: cocoa_id, index: NSInteger) -> cocoa_id {
+    let combo = unsafe { common::member_from_cocoa_id::<ComboBox>(this).unwrap() };
+    match combo.inner().inner().inner().inner().items.get(index as usize) {
+        Some(item) => unsafe { dropdown::item_title(item.native_id() as cocoa_id) },
+        None => nil,
+    }
+}
+/// Typed text no longer matches the picked item, which `NSComboBox` keeps selected on its own.
+extern "C" fn text_changed(this: &mut Object, _: Sel, _: cocoa_id) {
+    unsafe {
+        let selected: NSInteger = msg_send![this, indexOfSelectedItem];
+        if selected >= 0 {
+            let () = msg_send![this, deselectItemAtIndex: selected];
+        }
+    }
+}
+extern "C" fn selection_changed(this: &mut Object, _: Sel, _: cocoa_id) {
+    let combo = unsafe { common::member_from_cocoa_id_mut::<ComboBox>(this) }.unwrap();
+    let combo2 = unsafe { common::member_from_cocoa_id_mut::<ComboBox>(this) }.unwrap();
+    let i: NSInteger = unsafe { msg_send![this, indexOfSelectedItem] };
+    if i < 0 {
+        return;
+    }
+    let inner = combo.inner_mut().inner_mut().inner_mut().inner_mut();
+    let item_view = inner.items.get_mut(i as usize).unwrap();
+    if let Some(ref mut callback) = inner.on_select {
+        (callback.as_mut())(combo2, i as usize, item_view.as_mut());
+    }
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<ComboBox>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<ComboBox>(this).unwrap();
+        (b.inner().inner().inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut ComboBox, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<ComboBox>(param.width as u16, param.height as u16)
+    }
+}
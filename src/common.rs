@@ -50,8 +50,16 @@ impl NativeId for CocoaId {
 
 pub const IVAR: &str = "plyguiIvar";
 pub const IVAR_PARENT: &str = "plyguiIvarParent";
+pub const IVAR_HANDLERS: &str = "plyguiIvarHandlers";
 pub const DEFAULT_PADDING: i32 = 6;
 
+pub const NS_LINE_BREAK_BY_WORD_WRAPPING: NSUInteger = 0;
+pub const NS_LINE_BREAK_BY_CLIPPING: NSUInteger = 2;
+pub const NS_LINE_BREAK_BY_TRUNCATING_HEAD: NSUInteger = 3;
+pub const NS_LINE_BREAK_BY_TRUNCATING_TAIL: NSUInteger = 4;
+pub const NS_LINE_BREAK_BY_TRUNCATING_MIDDLE: NSUInteger = 5;
+const UNBOUNDED_TEXT_SIZE: f64 = 1.0e7;
+
 pub type ResizeHandler<O: controls::Control> = extern "C" fn(this: &mut O, _: Sel, param: NSSize);
 
 #[repr(C)]
@@ -114,15 +122,27 @@ impl<T: controls::Control + Sized> CocoaControlBase<T> {
     }
     pub fn draw(&mut self, coords: Option<(i32, i32)>, (width, height): (u16, u16)) {
         if let Some((x, y)) = coords {
-            if let Some(parent) = self.parent() {
-                let (_, ph) = parent.is_has_size().unwrap().size();
-                unsafe {
-                    let mut frame: NSRect = self.frame();
-                    frame.size = NSSize::new(width as f64, height as f64);
-                    frame.origin = NSPoint::new(x as f64, (ph as i32 - y - height as i32) as f64);
-                    //frame.origin = NSPoint::new(x as f64, y as f64);
-                    let () = msg_send![self.control, setFrame: frame];
+            // children of flipped superviews (scroll documents, tab pages, NSSplitView) take `y` as is
+            let flipped = unsafe {
+                let superview: cocoa_id = msg_send![self.control, superview];
+                !superview.is_null() && {
+                    let flipped: BOOL = msg_send![superview, isFlipped];
+                    flipped == YES
                 }
+            };
+            let y = if flipped {
+                y
+            } else if let Some(parent) = self.parent() {
+                let (_, ph) = parent.is_has_size().unwrap().size();
+                ph as i32 - y - height as i32
+            } else {
+                return;
+            };
+            unsafe {
+                let mut frame: NSRect = self.frame();
+                frame.size = NSSize::new(width as f64, height as f64);
+                frame.origin = NSPoint::new(x as f64, y as f64);
+                let () = msg_send![self.control, setFrame: frame];
             }
         }
     }
@@ -135,13 +155,17 @@ impl<T: controls::Control + Sized> CocoaControlBase<T> {
                 let mparent_type = mparent.as_any().type_id();
                 if let Some(control) = mparent.is_control_mut() {
                     control.invalidate();
+                } else if mparent_type == any::TypeId::of::<super::tray::TrayPopover>() {
+                    let popover = mparent.as_any_mut().downcast_mut::<super::tray::TrayPopover>().unwrap();
+                    popover.inner_mut().inner_mut().inner_mut().fit();
                 } else if mparent_type == any::TypeId::of::<super::window::Window>() {
                     this.draw(None);
                     unsafe {
                         let () = msg_send![parent_id, setNeedsDisplay: YES];
+                        crate::focus::update_key_view_loop(mparent);
                     }
                 } else {
-                    panic!("Parent member is unsupported, neither a control, nor a window");
+                    panic!("Parent member is unsupported, neither a control, nor a window, nor a popover");
                 }
             }
         }
@@ -158,6 +182,7 @@ impl<T: controls::Control + Sized> Drop for CocoaControlBase<T> {
     fn drop(&mut self) {
         unsafe {
             self.on_removed_from_container();
+            crate::events::free_handlers(self.control);
             let () = msg_send![self.control, release];
             //let () = msg_send![self.control, dealloc];
         }
@@ -175,7 +200,12 @@ pub unsafe fn parent_cocoa_id(id: cocoa_id, is_root: bool) -> Option<cocoa_id> {
         if YES == is_window {
             id
         } else {
-            msg_send![id, window]
+            let window: cocoa_id = msg_send![id, window];
+            if has_cocoa_id_ivar(window, IVAR).is_some() {
+                window
+            } else {
+                crate::tray::popover_root(id).unwrap_or(window)
+            }
         }
     } else if let Some(parent) = has_cocoa_id_ivar(id, IVAR_PARENT) {
         parent as cocoa_id
@@ -214,8 +244,19 @@ pub unsafe fn cast_cocoa_id_to_ptr<'a>(id: cocoa_id) -> Option<*mut c_void> {
     if let Some(parent) = has_cocoa_id_ivar(id, IVAR) {
         Some(parent)
     } else {
-        parent_cocoa_id(id, true).and_then(|id| cast_cocoa_id_to_ptr(id))
+        // windows without a member of their own (e.g. the one hosting a popover) are their own root
+        parent_cocoa_id(id, true).filter(|&root| root != id).and_then(|root| cast_cocoa_id_to_ptr(root))
+    }
+}
+
+/// Focus, input and appearance are only supported by views and windows, not e.g. by status items.
+pub unsafe fn is_view_or_window(id: cocoa_id) -> bool {
+    if id.is_null() {
+        return false;
     }
+    let is_view: BOOL = msg_send![id, isKindOfClass: class!(NSView)];
+    let is_window: BOOL = msg_send![id, isKindOfClass: class!(NSWindow)];
+    is_view == YES || is_window == YES
 }
 
 pub unsafe fn has_cocoa_id_ivar(id: cocoa_id, ivar: &str) -> Option<*mut c_void> {
@@ -238,16 +279,29 @@ pub unsafe fn has_cocoa_id_ivar(id: cocoa_id, ivar: &str) -> Option<*mut c_void>
 
 pub unsafe fn measure_string(text: &str) -> (u16, u16) {
     let title = NSString::alloc(cocoa::base::nil).init_str(text);
-    measure_nsstring(title)
+    measure_nsstring(title, nil, None)
 }
 
-pub unsafe fn measure_nsstring(title: cocoa_id) -> (u16, u16) {
+/// Measures the string as drawn with `font` (`nil` for the default one), word-wrapped to `width` unless it is `None`.
+pub unsafe fn measure_nsstring(title: cocoa_id, font: cocoa_id, width: Option<u16>) -> (u16, u16) {
+    let attributed: cocoa_id = msg_send![class!(NSAttributedString), alloc];
+    let attributed: cocoa_id = msg_send![attributed, initWithString:title attributes:crate::style::attributes(font, nil, None)];
+    let size = measure_attributed_constrained(attributed, width, NS_LINE_BREAK_BY_WORD_WRAPPING, 0);
+    let () = msg_send![attributed, release];
+    size
+}
+
+/// Measures the `NSAttributedString` laid out into `width` (unbounded if `None`) with the given `NSLineBreakMode`,
+/// using at most `max_lines` lines, `0` for no limit.
+pub unsafe fn measure_attributed_constrained(attributed: cocoa_id, width: Option<u16>, line_break_mode: NSUInteger, max_lines: NSUInteger) -> (u16, u16) {
     let text_storage: cocoa_id = msg_send![class!(NSTextStorage), alloc];
-    let text_storage: cocoa_id = msg_send![text_storage, initWithString: title];
+    let text_storage: cocoa_id = msg_send![text_storage, initWithAttributedString: attributed];
     let layout_manager: cocoa_id = msg_send![class!(NSLayoutManager), alloc];
     let layout_manager: cocoa_id = msg_send![layout_manager, init];
     let text_container: cocoa_id = msg_send![class!(NSTextContainer), alloc];
-    let text_container: cocoa_id = msg_send![text_container, init];
+    let text_container: cocoa_id = msg_send![text_container, initWithContainerSize: NSSize::new(width.map(|w| w as f64).unwrap_or(UNBOUNDED_TEXT_SIZE), UNBOUNDED_TEXT_SIZE)];
+    let () = msg_send![text_container, setLineBreakMode: line_break_mode];
+    let () = msg_send![text_container, setMaximumNumberOfLines: max_lines];
 
     let () = msg_send![layout_manager, addTextContainer: text_container];
     let () = msg_send![text_container, release];
@@ -258,22 +312,74 @@ pub unsafe fn measure_nsstring(title: cocoa_id) -> (u16, u16) {
     let range = NSRange::new(0, num);
 
     let string_rect: NSRect = msg_send![layout_manager, boundingRectForGlyphRange:range inTextContainer:text_container];
-    (string_rect.size.width as u16, string_rect.size.height as u16)
+    let () = msg_send![text_storage, release];
+    (string_rect.size.width.ceil() as u16, string_rect.size.height.ceil() as u16)
+}
+
+pub unsafe fn nsstring_to_string(string: cocoa_id) -> String {
+    if string.is_null() {
+        return String::new();
+    }
+    let chars: *const std::os::raw::c_char = msg_send![string, UTF8String];
+    ffi::CStr::from_ptr(chars).to_string_lossy().into_owned()
 }
 
 pub unsafe fn image_to_native(src: &image::DynamicImage) -> cocoa_id {
+    image_to_native_with_scale(src, 1.0)
+}
+
+/// Creates an `NSImage` from a bitmap drawn at `scale` pixels per point, e.g. `2.0` for a Retina asset.
+pub unsafe fn image_to_native_with_scale(src: &image::DynamicImage, scale: f64) -> cocoa_id {
+    image_reps_to_native(&[(src, scale)])
+}
+
+/// Creates an `NSImage` holding one bitmap representation per pixel density, the system draws the one
+/// matching the backing scale of the display. The point size is taken from the first representation.
+pub unsafe fn image_reps_to_native(reps: &[(&image::DynamicImage, f64)]) -> cocoa_id {
     use image::GenericImageView;
 
-    let size = src.dimensions();
+    let size = reps.first().map(|&(src, scale)| {
+        let (w, h) = src.dimensions();
+        NSSize::new(w as f64 / scale, h as f64 / scale)
+    }).unwrap_or(NSSize::new(0.0, 0.0));
+    let img: cocoa_id = msg_send![class!(NSImage), alloc];
+    let img: cocoa_id = msg_send![img, initWithSize: size];
+    for &(src, _) in reps {
+        let rep: cocoa_id = msg_send![class!(NSBitmapImageRep), alloc];
+        let rep: cocoa_id = msg_send![rep, initWithCGImage: image_to_cgimage(src)];
+        let () = msg_send![rep, setSize: size];
+        let () = msg_send![img, addRepresentation: rep];
+        let () = msg_send![rep, release];
+    }
+    img
+}
+
+unsafe fn image_to_cgimage(src: &image::DynamicImage) -> CGImage {
+    use image::GenericImageView;
 
+    let size = src.dimensions();
     let color_space = CGColorSpace::create_device_rgb();
     let provider = CGDataProvider::from_buffer(Arc::new(src.to_rgba().into_raw()));
-    let cgimage = CGImage::new(size.0 as usize, size.1 as usize, 8, 32, 4 * size.0 as usize, &color_space, kCGBitmapByteOrderDefault | kCGImageAlphaLast, &provider, true, 0);
+    CGImage::new(size.0 as usize, size.1 as usize, 8, 32, 4 * size.0 as usize, &color_space, kCGBitmapByteOrderDefault | kCGImageAlphaLast, &provider, true, 0)
+}
 
-    let img: cocoa_id = msg_send![class!(NSImage), alloc];
-    let size = NSSize::new(size.0 as f64, size.1 as f64);
-    let () = msg_send![img, initWithCGImage:cgimage size:size];
-    img
+/// Pixels per point of the screen `view` is shown on, or of the main screen if it is not in a window yet.
+pub unsafe fn backing_scale_factor(view: cocoa_id) -> f64 {
+    let window: cocoa_id = if view.is_null() { nil } else { msg_send![view, window] };
+    if !window.is_null() {
+        return msg_send![window, backingScaleFactor];
+    }
+    let screen: cocoa_id = msg_send![class!(NSScreen), mainScreen];
+    if screen.is_null() {
+        1.0
+    } else {
+        msg_send![screen, backingScaleFactor]
+    }
+}
+/// Parses a numeric markup attribute, `None` if it is absent or not a number.
+#[cfg(feature = "markup")]
+pub fn markup_number(markup: &plygui_api::markup::Markup, attribute: &str) -> Option<f64> {
+    markup.attributes.get(attribute).and_then(|node| node.as_attribute().trim().parse().ok())
 }
 
 pub unsafe fn register_window_class<F>(name: &str, base: &str, mut f: F) -> RefClass
@@ -285,6 +391,7 @@ where
 
     decl.add_ivar::<*mut c_void>(IVAR);
     decl.add_ivar::<*mut c_void>(IVAR_PARENT);
+    decl.add_ivar::<*mut c_void>(IVAR_HANDLERS);
 
     decl.add_method(sel!(translatesAutoresizingMaskIntoConstraints:), class_autoresizing as extern "C" fn(&mut Object, Sel, cocoa_id) -> BOOL);
     decl.add_method(sel!(requiresConstraintBasedLayout:), class_constraint_layout as extern "C" fn(&mut Object, Sel, cocoa_id) -> BOOL);
@@ -292,7 +399,24 @@ where
 
     f(&mut decl);
 
-    RefClass(decl.register())
+    let class = decl.register();
+    // input, focus and appearance only make sense for views and windows, not menu items, targets or the application
+    if is_kind_of_class(class, "NSView") || is_kind_of_class(class, "NSWindow") {
+        crate::events::install(class);
+        crate::focus::install(class);
+        crate::appearance::install(class);
+    }
+    RefClass(class)
+}
+fn is_kind_of_class(class: &Class, name: &str) -> bool {
+    let mut class = Some(class);
+    while let Some(current) = class {
+        if current.name() == name {
+            return true;
+        }
+        class = current.superclass();
+    }
+    false
 }
 pub unsafe fn make_menu(menu: cocoa_id, mut items: Vec<types::MenuItem>, storage: &mut HashMap<cocoa_id, callbacks::Action>, item_spawn: unsafe fn(title: cocoa_id, selfptr: *mut c_void) -> cocoa_id, selfptr: *mut c_void) {
     let mut none = Vec::new();
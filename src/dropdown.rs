@@ -0,0 +1,361 @@
+use crate::common::{self, *};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiDropdown", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(onItemSelected:), item_selected as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSPopUpButton";
+
+pub type Dropdown = AMember<AControl<AContainer<AAdapted<CocoaDropdown>>>>;
+
+/// Receives the index of the item picked by the user and its view.
+pub type OnSelect = Box<dyn FnMut(&mut dyn controls::Member, usize, &mut dyn controls::Control)>;
+
+/// Adapter item views are not shown by the dropdown itself, the entry titles are taken from their text.
+#[repr(C)]
+pub struct CocoaDropdown {
+    base: common::CocoaControlBase<Dropdown>,
+    items: Vec<Box<dyn controls::Control>>,
+    on_select: Option<OnSelect>,
+}
+
+pub trait DropdownExt {
+    fn selected(&self) -> Option<usize>;
+    /// Does not call the `on_select` callback.
+    fn select(&mut self, index: Option<usize>);
+    fn on_select(&mut self, callback: Option<OnSelect>);
+}
+
+impl DropdownExt for Dropdown {
+    fn selected(&self) -> Option<usize> {
+        let index: NSInteger = unsafe { msg_send![self.inner().inner().inner().inner().base.control, indexOfSelectedItem] };
+        if index < 0 { None } else { Some(index as usize) }
+    }
+    fn select(&mut self, index: Option<usize>) {
+        let dropdown = self.inner_mut().inner_mut().inner_mut().inner_mut();
+        let index = index.filter(|index| *index < dropdown.items.len()).map(|index| index as NSInteger).unwrap_or(-1);
+        unsafe {
+            let () = msg_send![dropdown.base.control, selectItemAtIndex: index];
+        }
+    }
+    fn on_select(&mut self, callback: Option<OnSelect>) {
+        self.inner_mut().inner_mut().inner_mut().inner_mut().on_select = callback;
+    }
+}
+
+impl CocoaDropdown {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<Dropdown>) -> Self {
+        let d = CocoaDropdown {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            items: Vec::new(),
+            on_select: None,
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *d.base.control).set_ivar(common::IVAR, selfptr);
+            let () = msg_send![d.base.control, setPullsDown: NO];
+            let () = msg_send![d.base.control, setTarget: d.base.control];
+            let () = msg_send![d.base.control, setAction: sel!(onItemSelected:)];
+        }
+        d
+    }
+    pub fn with_adapter(adapter: Box<dyn types::Adapter>) -> Box<Dropdown> {
+        let mut b: Box<mem::MaybeUninit<Dropdown>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                AContainer::with_inner(
+                    AAdapted::with_inner(
+                        Self::with_uninit(b.as_mut()),
+                        adapter,
+                        &mut b,
+                    ),
+                )
+            ),
+        );
+        unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        }
+    }
+    /// Menu entries copy the item titles, picks up the ones changed since.
+    fn refresh_titles(&self) {
+        for (i, item) in self.items.iter().enumerate() {
+            unsafe {
+                let entry: cocoa_id = msg_send![self.base.control, itemAtIndex: i as NSInteger];
+                let () = msg_send![entry, setTitle: item_title(item.native_id() as cocoa_id)];
+            }
+        }
+    }
+    fn add_item_inner(&mut self, base: &mut MemberBase, indexes: &[usize]) {
+        let i = indexes[0];
+        let (member, control, adapter, _) = unsafe { Dropdown::adapter_base_parts_mut(base) };
+        let (pw, ph) = control.measured;
+        let this: &mut Dropdown = unsafe { utils::base_to_impl_mut(member) };
+
+        let mut item = adapter.adapter.spawn_item_view(indexes, this).unwrap();
+        item.on_added_to_container(this, 0, 0, utils::coord_to_size(pw as i32) as u16, utils::coord_to_size(ph as i32) as u16);
+        unsafe {
+            // titles may repeat, so the menu is edited directly instead of `insertItemWithTitle:atIndex:`
+            let menu: cocoa_id = msg_send![self.base.control, menu];
+            let no_key: cocoa_id = msg_send![class!(NSString), string];
+            let entry: cocoa_id = msg_send![class!(NSMenuItem), alloc];
+            let entry: cocoa_id = msg_send![entry, initWithTitle:item_title(item.native_id() as cocoa_id) action:nil keyEquivalent:no_key];
+            let () = msg_send![menu, insertItem:entry atIndex:i as NSInteger];
+            let () = msg_send![entry, release];
+        }
+        self.items.insert(i, item);
+    }
+    fn remove_item_inner(&mut self, base: &mut MemberBase, indexes: &[usize]) {
+        let this: &mut Dropdown = unsafe { utils::base_to_impl_mut(base) };
+        unsafe {
+            let () = msg_send![self.base.control, removeItemAtIndex: indexes[0] as NSInteger];
+        }
+        self.items.remove(indexes[0]).on_removed_from_container(this);
+    }
+}
+
+/// Visible text of a native item view, empty if it has none.
+pub(crate) unsafe fn item_title(view: cocoa_id) -> cocoa_id {
+    let responds = |selector: Sel| -> bool {
+        let responds: BOOL = msg_send![view, respondsToSelector: selector];
+        responds == YES
+    };
+    if responds(sel!(string)) {
+        msg_send![view, string]
+    } else if responds(sel!(title)) {
+        msg_send![view, title]
+    } else if responds(sel!(stringValue)) {
+        msg_send![view, stringValue]
+    } else {
+        msg_send![class!(NSString), string]
+    }
+}
+
+impl AdaptedInner for CocoaDropdown {
+    fn on_item_change(&mut self, base: &mut MemberBase, value: adapter::Change) {
+        let selected: NSInteger = unsafe { msg_send![self.base.control, indexOfSelectedItem] };
+        match value {
+            adapter::Change::Added(at, _) => {
+                self.add_item_inner(base, at);
+            },
+            adapter::Change::Removed(at) => {
+                self.remove_item_inner(base, at);
+            },
+            adapter::Change::Edited(at, _) => {
+                self.remove_item_inner(base, at);
+                self.add_item_inner(base, at);
+                unsafe {
+                    let () = msg_send![self.base.control, selectItemAtIndex: selected];
+                }
+            },
+        }
+        self.base.invalidate();
+    }
+}
+
+impl ContainerInner for CocoaDropdown {
+    fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
+        for child in self.items.as_mut_slice() {
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member_mut().id() == *id {
+                        return Some(child.as_mut());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container_mut() {
+                let ret = c.find_control_mut(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+    fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
+        for child in self.items.as_slice() {
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member().id() == *id {
+                        return Some(child.as_ref());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container() {
+                let ret = c.find_control(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+}
+
+impl ControlInner for CocoaDropdown {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, x: i32, y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        control.coords = Some((x, y));
+
+        let (member, _, adapter, _) = unsafe { Dropdown::adapter_base_parts_mut(member) };
+
+        adapter.adapter.for_each(&mut (|indexes, _node| {
+            self.add_item_inner(member, indexes);
+        }));
+        self.base.invalidate();
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        unsafe {
+            let () = msg_send![self.base.control, removeAllItems];
+        }
+        let this: &Dropdown = unsafe { common::member_from_cocoa_id(self.base.control).unwrap() };
+        for mut child in self.items.drain(..) {
+            child.on_removed_from_container(this);
+        }
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, Dropdown, ["Dropdown"]);
+    }
+}
+
+impl HasLayoutInner for CocoaDropdown {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl HasNativeIdInner for CocoaDropdown {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaDropdown {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaDropdown {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaDropdown {}
+
+impl Drawable for CocoaDropdown {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                // fits the longest title
+                let fitting: NSSize = unsafe { msg_send![self.base.control, fittingSize] };
+                let w = match control.layout.width {
+                    layout::Size::MatchParent => parent_width as i32,
+                    layout::Size::Exact(w) => w as i32,
+                    layout::Size::WrapContent => cmp::max(fitting.width as i32, defaults::THE_ULTIMATE_ANSWER_TO_EVERYTHING as i32),
+                };
+                let h = match control.layout.height {
+                    layout::Size::MatchParent => parent_height as i32,
+                    layout::Size::Exact(h) => h as i32,
+                    layout::Size::WrapContent => fitting.height as i32,
+                };
+                (cmp::max(0, w) as u16, cmp::max(0, h) as u16)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.refresh_titles();
+        self.base.invalidate();
+    }
+}
+
+impl Drop for CocoaDropdown {
+    fn drop(&mut self) {
+        let this: &Dropdown = unsafe { common::member_from_cocoa_id(self.base.control).unwrap() };
+        for ref mut child in self.items.as_mut_slice() {
+            child.on_removed_from_container(this);
+        }
+    }
+}
+
+impl Spawnable for CocoaDropdown {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_adapter(Box::new(types::imp::StringVecAdapter::<crate::imp::Text>::new()))
+    }
+}
+
+extern "C" fn item_selected(this: &mut Object, _: Sel, _: cocoa_id) {
+    let dropdown = unsafe { common::member_from_cocoa_id_mut::<Dropdown>(this) }.unwrap();
+    let dropdown2 = unsafe { common::member_from_cocoa_id_mut::<Dropdown>(this) }.unwrap();
+    let i: NSInteger = unsafe { msg_send![this, indexOfSelectedItem] };
+    if i < 0 {
+        return;
+    }
+    let inner = dropdown.inner_mut().inner_mut().inner_mut().inner_mut();
+    let item_view = inner.items.get_mut(i as usize).unwrap();
+    if let Some(ref mut callback) = inner.on_select {
+        (callback.as_mut())(dropdown2, i as usize, item_view.as_mut());
+    }
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<Dropdown>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<Dropdown>(this).unwrap();
+        (b.inner().inner().inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut Dropdown, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<Dropdown>(param.width as u16, param.height as u16)
+    }
+}
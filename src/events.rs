@@ -0,0 +1,506 @@
+use crate::common::{self, *};
+
+use objc::runtime::{self, Imp};
+use objc::Encode;
+
+const NS_TRACKING_MOUSE_ENTERED_AND_EXITED: NSUInteger = 0x01;
+const NS_TRACKING_MOUSE_MOVED: NSUInteger = 0x02;
+const NS_TRACKING_ACTIVE_IN_KEY_WINDOW: NSUInteger = 0x20;
+const NS_TRACKING_IN_VISIBLE_RECT: NSUInteger = 0x200;
+
+const NS_EVENT_MODIFIER_CAPS_LOCK: NSUInteger = 1 << 16;
+const NS_EVENT_MODIFIER_SHIFT: NSUInteger = 1 << 17;
+const NS_EVENT_MODIFIER_CONTROL: NSUInteger = 1 << 18;
+const NS_EVENT_MODIFIER_OPTION: NSUInteger = 1 << 19;
+const NS_EVENT_MODIFIER_COMMAND: NSUInteger = 1 << 20;
+const NS_EVENT_MODIFIER_FUNCTION: NSUInteger = 1 << 23;
+
+/// Modifier keys held during an input event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub option: bool,
+    pub command: bool,
+    pub caps_lock: bool,
+    pub function: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Other(isize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+    /// Hardware-independent virtual key code, see `kVK_*` constants of Carbon `Events.h`.
+    pub key_code: u16,
+    /// Characters produced by the key, with modifiers applied.
+    pub characters: String,
+    /// Characters produced by the key, as if no modifiers were held.
+    pub characters_ignoring_modifiers: String,
+    pub modifiers: Modifiers,
+    pub is_repeat: bool,
+}
+
+/// Mouse event with coordinates local to the control, origin at the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseEvent {
+    /// `None` for moves, enters and leaves.
+    pub button: Option<MouseButton>,
+    pub x: f64,
+    pub y: f64,
+    pub click_count: isize,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollEvent {
+    pub delta_x: f64,
+    pub delta_y: f64,
+    /// `true` for trackpads and Magic Mouse, where deltas are in points rather than lines.
+    pub precise: bool,
+    pub x: f64,
+    pub y: f64,
+    pub modifiers: Modifiers,
+}
+
+/// Returning `true` consumes the event, otherwise it goes on to the default native handling.
+pub type OnKey = Box<dyn FnMut(&mut dyn controls::Member, &KeyEvent) -> bool>;
+/// Returning `true` consumes the event, otherwise it goes on to the default native handling.
+pub type OnMouse = Box<dyn FnMut(&mut dyn controls::Member, &MouseEvent) -> bool>;
+/// Returning `true` consumes the event, otherwise it goes on to the default native handling.
+pub type OnScroll = Box<dyn FnMut(&mut dyn controls::Member, &ScrollEvent) -> bool>;
+
+/// Per-view callbacks, kept behind the `common::IVAR_HANDLERS` ivar of every class created with `common::register_window_class`.
+pub struct Handlers {
+    pub(crate) on_key_down: Option<OnKey>,
+    pub(crate) on_key_up: Option<OnKey>,
+    pub(crate) on_mouse_down: Option<OnMouse>,
+    pub(crate) on_mouse_up: Option<OnMouse>,
+    pub(crate) on_mouse_move: Option<OnMouse>,
+    pub(crate) on_mouse_enter: Option<OnMouse>,
+    pub(crate) on_mouse_leave: Option<OnMouse>,
+    pub(crate) on_scroll: Option<OnScroll>,
+    pub(crate) on_focus_gained: Option<crate::focus::OnFocus>,
+    pub(crate) on_focus_lost: Option<crate::focus::OnFocus>,
+    /// Member explicitly set as the next key view, `None` to follow the container child order.
+    pub(crate) next_focus: Option<ids::Id>,
+    pub(crate) on_appearance_changed: Option<crate::appearance::OnAppearanceChanged>,
+    /// Last delivered appearance, `Some` while the view observes its appearance.
+    pub(crate) observed_appearance: Option<crate::appearance::Appearance>,
+    tracking_area: cocoa_id,
+}
+
+impl Default for Handlers {
+    fn default() -> Self {
+        Handlers {
+            on_key_down: None,
+            on_key_up: None,
+            on_mouse_down: None,
+            on_mouse_up: None,
+            on_mouse_move: None,
+            on_mouse_enter: None,
+            on_mouse_leave: None,
+            on_scroll: None,
+            on_focus_gained: None,
+            on_focus_lost: None,
+            next_focus: None,
+            on_appearance_changed: None,
+            observed_appearance: None,
+            tracking_area: nil,
+        }
+    }
+}
+
+impl Handlers {
+    fn has_key_handlers(&self) -> bool {
+        self.on_key_down.is_some() || self.on_key_up.is_some()
+    }
+    fn needs_tracking(&self) -> bool {
+        self.on_mouse_move.is_some() || self.on_mouse_enter.is_some() || self.on_mouse_leave.is_some()
+    }
+}
+
+/// Keyboard and mouse callbacks available on every control and window of this backend.
+pub trait HasInputEvents {
+    fn on_key_down(&mut self, callback: Option<OnKey>);
+    fn on_key_up(&mut self, callback: Option<OnKey>);
+    fn on_mouse_down(&mut self, callback: Option<OnMouse>);
+    fn on_mouse_up(&mut self, callback: Option<OnMouse>);
+    fn on_mouse_move(&mut self, callback: Option<OnMouse>);
+    fn on_mouse_enter(&mut self, callback: Option<OnMouse>);
+    fn on_mouse_leave(&mut self, callback: Option<OnMouse>);
+    fn on_scroll(&mut self, callback: Option<OnScroll>);
+}
+
+impl<T: controls::Member + ?Sized> HasInputEvents for T {
+    fn on_key_down(&mut self, callback: Option<OnKey>) {
+        if let Some(handlers) = unsafe { handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_key_down = callback;
+        }
+    }
+    fn on_key_up(&mut self, callback: Option<OnKey>) {
+        if let Some(handlers) = unsafe { handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_key_up = callback;
+        }
+    }
+    fn on_mouse_down(&mut self, callback: Option<OnMouse>) {
+        if let Some(handlers) = unsafe { handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_mouse_down = callback;
+        }
+    }
+    fn on_mouse_up(&mut self, callback: Option<OnMouse>) {
+        if let Some(handlers) = unsafe { handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_mouse_up = callback;
+        }
+    }
+    fn on_mouse_move(&mut self, callback: Option<OnMouse>) {
+        let id = self.native_id() as cocoa_id;
+        unsafe {
+            if let Some(handlers) = handlers_or_default(id) {
+                handlers.on_mouse_move = callback;
+            }
+            update_tracking_area(id);
+        }
+    }
+    fn on_mouse_enter(&mut self, callback: Option<OnMouse>) {
+        let id = self.native_id() as cocoa_id;
+        unsafe {
+            if let Some(handlers) = handlers_or_default(id) {
+                handlers.on_mouse_enter = callback;
+            }
+            update_tracking_area(id);
+        }
+    }
+    fn on_mouse_leave(&mut self, callback: Option<OnMouse>) {
+        let id = self.native_id() as cocoa_id;
+        unsafe {
+            if let Some(handlers) = handlers_or_default(id) {
+                handlers.on_mouse_leave = callback;
+            }
+            update_tracking_area(id);
+        }
+    }
+    fn on_scroll(&mut self, callback: Option<OnScroll>) {
+        if let Some(handlers) = unsafe { handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_scroll = callback;
+        }
+    }
+}
+
+pub unsafe fn handlers<'a>(id: cocoa_id) -> Option<&'a mut Handlers> {
+    common::has_cocoa_id_ivar(id, common::IVAR_HANDLERS).map(|ptr| &mut *(ptr as *mut Handlers))
+}
+
+/// Returns the handlers of the view, allocating them on first use. `None` if the view class was not created with `common::register_window_class`.
+pub unsafe fn handlers_or_default<'a>(id: cocoa_id) -> Option<&'a mut Handlers> {
+    if id.is_null() {
+        return None;
+    }
+    if let Some(handlers) = handlers(id) {
+        return Some(handlers);
+    }
+    let class: *const Class = msg_send![id, class];
+    if (&*class).instance_variable(common::IVAR_HANDLERS).is_none() {
+        return None;
+    }
+    let handlers = Box::into_raw(Box::new(Handlers::default()));
+    (&mut *id).set_ivar(common::IVAR_HANDLERS, handlers as *mut c_void);
+    Some(&mut *handlers)
+}
+
+/// Releases the handlers of the view, must be called once the owning member is destroyed.
+pub unsafe fn free_handlers(id: cocoa_id) {
+    if let Some(ptr) = common::has_cocoa_id_ivar(id, common::IVAR_HANDLERS) {
+        crate::appearance::stop_observing(id);
+        let handlers = Box::from_raw(ptr as *mut Handlers);
+        if !handlers.tracking_area.is_null() {
+            let () = msg_send![id, removeTrackingArea: handlers.tracking_area];
+            let () = msg_send![handlers.tracking_area, release];
+        }
+        (&mut *id).set_ivar(common::IVAR_HANDLERS, ptr::null_mut::<c_void>());
+    }
+}
+
+/// Adds the generic event methods to a freshly registered class. Selectors already declared by the class itself are left untouched,
+/// such classes call `handle_*` functions from their own implementations.
+pub(crate) unsafe fn install(class: *const Class) {
+    let class = class as *mut Class;
+    add_event_method(class, sel!(keyDown:), key_down);
+    add_event_method(class, sel!(keyUp:), key_up);
+    add_event_method(class, sel!(mouseDown:), mouse_down);
+    add_event_method(class, sel!(mouseUp:), mouse_up);
+    add_event_method(class, sel!(rightMouseDown:), right_mouse_down);
+    add_event_method(class, sel!(rightMouseUp:), right_mouse_up);
+    add_event_method(class, sel!(otherMouseDown:), other_mouse_down);
+    add_event_method(class, sel!(otherMouseUp:), other_mouse_up);
+    add_event_method(class, sel!(mouseMoved:), mouse_moved);
+    add_event_method(class, sel!(mouseDragged:), mouse_dragged);
+    add_event_method(class, sel!(mouseEntered:), mouse_entered);
+    add_event_method(class, sel!(mouseExited:), mouse_exited);
+    add_event_method(class, sel!(scrollWheel:), scroll_wheel);
+
+    let types = ffi::CString::new(format!("{}{}{}", <BOOL>::encode().as_str(), <*mut Object>::encode().as_str(), <Sel>::encode().as_str())).unwrap();
+    let imp: Imp = mem::transmute(accepts_first_responder as extern "C" fn(&mut Object, Sel) -> BOOL);
+    runtime::class_addMethod(class, sel!(acceptsFirstResponder), imp, types.as_ptr());
+}
+
+/// Dispatches a key press to the member of `this`, returns `true` if consumed.
+pub(crate) unsafe fn handle_key_down(this: &mut Object, event: cocoa_id) -> bool {
+    dispatch_key(this, event, |h| h.on_key_down.as_mut())
+}
+/// Dispatches a mouse button press to the member of `this`, returns `true` if consumed.
+pub(crate) unsafe fn handle_mouse_down(this: &mut Object, event: cocoa_id) -> bool {
+    dispatch_mouse(this, event, true, |h| h.on_mouse_down.as_mut())
+}
+/// Dispatches a mouse button release to the member of `this`, returns `true` if consumed.
+pub(crate) unsafe fn handle_mouse_up(this: &mut Object, event: cocoa_id) -> bool {
+    dispatch_mouse(this, event, true, |h| h.on_mouse_up.as_mut())
+}
+
+unsafe fn add_event_method(class: *mut Class, sel: Sel, imp: extern "C" fn(&mut Object, Sel, cocoa_id)) {
+    let types = ffi::CString::new(format!("{}{}{}{}", <()>::encode().as_str(), <*mut Object>::encode().as_str(), <Sel>::encode().as_str(), <cocoa_id>::encode().as_str())).unwrap();
+    runtime::class_addMethod(class, sel, mem::transmute::<_, Imp>(imp), types.as_ptr());
+}
+
+unsafe fn update_tracking_area(id: cocoa_id) {
+    if let Some(handlers) = handlers(id) {
+        let needs_tracking = handlers.needs_tracking();
+        if needs_tracking && handlers.tracking_area.is_null() {
+            let options = NS_TRACKING_MOUSE_ENTERED_AND_EXITED | NS_TRACKING_MOUSE_MOVED | NS_TRACKING_ACTIVE_IN_KEY_WINDOW | NS_TRACKING_IN_VISIBLE_RECT;
+            let area: cocoa_id = msg_send![class!(NSTrackingArea), alloc];
+            let area: cocoa_id = msg_send![area, initWithRect:NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0)) options:options owner:id userInfo:nil];
+            let () = msg_send![id, addTrackingArea: area];
+            handlers.tracking_area = area;
+        } else if !needs_tracking && !handlers.tracking_area.is_null() {
+            let () = msg_send![id, removeTrackingArea: handlers.tracking_area];
+            let () = msg_send![handlers.tracking_area, release];
+            handlers.tracking_area = nil;
+        }
+    }
+}
+
+pub(crate) unsafe fn member_from_cocoa_id_mut<'a>(id: cocoa_id) -> Option<&'a mut dyn controls::Member> {
+    common::member_base_from_cocoa_id_mut(id).map(|base| base.as_member_mut())
+}
+
+pub(crate) unsafe fn superclass(this: &Object) -> &'static Class {
+    let class: *const Class = msg_send![this, class];
+    (&*class).superclass().unwrap()
+}
+
+unsafe fn modifiers(event: cocoa_id) -> Modifiers {
+    let flags: NSUInteger = msg_send![event, modifierFlags];
+    Modifiers {
+        shift: flags & NS_EVENT_MODIFIER_SHIFT != 0,
+        control: flags & NS_EVENT_MODIFIER_CONTROL != 0,
+        option: flags & NS_EVENT_MODIFIER_OPTION != 0,
+        command: flags & NS_EVENT_MODIFIER_COMMAND != 0,
+        caps_lock: flags & NS_EVENT_MODIFIER_CAPS_LOCK != 0,
+        function: flags & NS_EVENT_MODIFIER_FUNCTION != 0,
+    }
+}
+
+unsafe fn local_position(this: &Object, event: cocoa_id) -> (f64, f64) {
+    let position: NSPoint = msg_send![event, locationInWindow];
+    let is_window: BOOL = msg_send![this, isKindOfClass: class!(NSWindow)];
+    let (view, position): (cocoa_id, NSPoint) = if is_window == YES {
+        (msg_send![this, contentView], position)
+    } else {
+        let view = this as *const _ as cocoa_id;
+        (view, msg_send![view, convertPoint:position fromView:nil])
+    };
+    let flipped: BOOL = msg_send![view, isFlipped];
+    if flipped == YES {
+        (position.x, position.y)
+    } else {
+        let bounds: NSRect = msg_send![view, bounds];
+        (position.x, bounds.size.height - position.y)
+    }
+}
+
+unsafe fn dispatch_key<F>(this: &mut Object, event: cocoa_id, f: F) -> bool
+where
+    F: FnOnce(&mut Handlers) -> Option<&mut OnKey>,
+{
+    if event.is_null() {
+        return false;
+    }
+    let id = this as *mut Object;
+    if let Some(callback) = handlers(id).and_then(f) {
+        let repeat: BOOL = msg_send![event, isARepeat];
+        let key = KeyEvent {
+            key_code: msg_send![event, keyCode],
+            characters: common::nsstring_to_string(msg_send![event, characters]),
+            characters_ignoring_modifiers: common::nsstring_to_string(msg_send![event, charactersIgnoringModifiers]),
+            modifiers: modifiers(event),
+            is_repeat: repeat == YES,
+        };
+        if let Some(member) = member_from_cocoa_id_mut(id) {
+            return (callback.as_mut())(member, &key);
+        }
+    }
+    false
+}
+
+unsafe fn dispatch_mouse<F>(this: &mut Object, event: cocoa_id, with_button: bool, f: F) -> bool
+where
+    F: FnOnce(&mut Handlers) -> Option<&mut OnMouse>,
+{
+    if event.is_null() {
+        return false;
+    }
+    let id = this as *mut Object;
+    if let Some(callback) = handlers(id).and_then(f) {
+        let (x, y) = local_position(this, event);
+        let button = if with_button {
+            let number: NSInteger = msg_send![event, buttonNumber];
+            Some(match number {
+                0 => MouseButton::Left,
+                1 => MouseButton::Right,
+                other => MouseButton::Other(other as isize),
+            })
+        } else {
+            None
+        };
+        let click_count: NSInteger = if with_button { msg_send![event, clickCount] } else { 0 };
+        let mouse = MouseEvent {
+            button: button,
+            x: x,
+            y: y,
+            click_count: click_count as isize,
+            modifiers: modifiers(event),
+        };
+        if let Some(member) = member_from_cocoa_id_mut(id) {
+            return (callback.as_mut())(member, &mouse);
+        }
+    }
+    false
+}
+
+unsafe fn dispatch_scroll(this: &mut Object, event: cocoa_id) -> bool {
+    if event.is_null() {
+        return false;
+    }
+    let id = this as *mut Object;
+    if let Some(callback) = handlers(id).and_then(|h| h.on_scroll.as_mut()) {
+        let (x, y) = local_position(this, event);
+        let precise: BOOL = msg_send![event, hasPreciseScrollingDeltas];
+        let scroll = ScrollEvent {
+            delta_x: msg_send![event, scrollingDeltaX],
+            delta_y: msg_send![event, scrollingDeltaY],
+            precise: precise == YES,
+            x: x,
+            y: y,
+            modifiers: modifiers(event),
+        };
+        if let Some(member) = member_from_cocoa_id_mut(id) {
+            return (callback.as_mut())(member, &scroll);
+        }
+    }
+    false
+}
+
+extern "C" fn accepts_first_responder(this: &mut Object, _: Sel) -> BOOL {
+    unsafe {
+        if handlers(this).map(|h| h.has_key_handlers()).unwrap_or(false) {
+            YES
+        } else {
+            msg_send![super(this, superclass(this)), acceptsFirstResponder]
+        }
+    }
+}
+extern "C" fn key_down(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_key_down(this, event) {
+            let () = msg_send![super(this, superclass(this)), keyDown: event];
+        }
+    }
+}
+extern "C" fn key_up(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_key(this, event, |h| h.on_key_up.as_mut()) {
+            let () = msg_send![super(this, superclass(this)), keyUp: event];
+        }
+    }
+}
+extern "C" fn mouse_down(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_down(this, event) {
+            let () = msg_send![super(this, superclass(this)), mouseDown: event];
+        }
+    }
+}
+extern "C" fn mouse_up(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_up(this, event) {
+            let () = msg_send![super(this, superclass(this)), mouseUp: event];
+        }
+    }
+}
+extern "C" fn right_mouse_down(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_down(this, event) {
+            let () = msg_send![super(this, superclass(this)), rightMouseDown: event];
+        }
+    }
+}
+extern "C" fn right_mouse_up(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_up(this, event) {
+            let () = msg_send![super(this, superclass(this)), rightMouseUp: event];
+        }
+    }
+}
+extern "C" fn other_mouse_down(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_down(this, event) {
+            let () = msg_send![super(this, superclass(this)), otherMouseDown: event];
+        }
+    }
+}
+extern "C" fn other_mouse_up(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !handle_mouse_up(this, event) {
+            let () = msg_send![super(this, superclass(this)), otherMouseUp: event];
+        }
+    }
+}
+extern "C" fn mouse_moved(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_mouse(this, event, false, |h| h.on_mouse_move.as_mut()) {
+            let () = msg_send![super(this, superclass(this)), mouseMoved: event];
+        }
+    }
+}
+extern "C" fn mouse_dragged(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_mouse(this, event, false, |h| h.on_mouse_move.as_mut()) {
+            let () = msg_send![super(this, superclass(this)), mouseDragged: event];
+        }
+    }
+}
+extern "C" fn mouse_entered(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_mouse(this, event, false, |h| h.on_mouse_enter.as_mut()) {
+            let () = msg_send![super(this, superclass(this)), mouseEntered: event];
+        }
+    }
+}
+extern "C" fn mouse_exited(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_mouse(this, event, false, |h| h.on_mouse_leave.as_mut()) {
+            let () = msg_send![super(this, superclass(this)), mouseExited: event];
+        }
+    }
+}
+extern "C" fn scroll_wheel(this: &mut Object, _: Sel, event: cocoa_id) {
+    unsafe {
+        if !dispatch_scroll(this, event) {
+            let () = msg_send![super(this, superclass(this)), scrollWheel: event];
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::common::{self, *};
+use crate::events;
+
+use objc::runtime::{self, Imp};
+use objc::Encode;
+
+pub type OnFocus = Box<dyn FnMut(&mut dyn controls::Member)>;
+
+/// First responder management for controls and windows of this backend.
+///
+/// The key view loop (Tab / Shift+Tab navigation) follows the container child order automatically,
+/// `set_next_focus` overrides it for a single member. Members that are not backed by a view or window, like trays, never take focus.
+pub trait HasFocus {
+    /// Moves keyboard focus to the member, returns `false` if it refuses to accept it.
+    fn focus(&mut self) -> bool;
+    fn has_focus(&self) -> bool;
+    /// Focus callbacks do not make the member focusable, they only fire for members that accept focus on their own or handle keys.
+    fn on_focus_gained(&mut self, callback: Option<OnFocus>);
+    fn on_focus_lost(&mut self, callback: Option<OnFocus>);
+    /// Member to receive focus after this one on Tab, `None` to return to the container child order.
+    fn set_next_focus(&mut self, next: Option<&dyn controls::Member>);
+}
+
+impl<T: controls::Member + ?Sized> HasFocus for T {
+    fn focus(&mut self) -> bool {
+        unsafe {
+            let id = self.native_id() as cocoa_id;
+            if !common::is_view_or_window(id) {
+                return false;
+            }
+            if is_window(id) {
+                let () = msg_send![id, makeKeyAndOrderFront: nil];
+                return true;
+            }
+            let window: cocoa_id = msg_send![id, window];
+            if window.is_null() {
+                return false;
+            }
+            let focused: BOOL = msg_send![window, makeFirstResponder: key_view_target(id)];
+            focused == YES
+        }
+    }
+    fn has_focus(&self) -> bool {
+        unsafe {
+            let id = self.native_id() as cocoa_id;
+            if !common::is_view_or_window(id) {
+                return false;
+            }
+            if is_window(id) {
+                let key: BOOL = msg_send![id, isKeyWindow];
+                return key == YES;
+            }
+            let window: cocoa_id = msg_send![id, window];
+            if window.is_null() {
+                return false;
+            }
+            let target = key_view_target(id);
+            let responder: cocoa_id = msg_send![window, firstResponder];
+            if responder == target {
+                return true;
+            }
+            // Text fields hand the focus over to the shared field editor of the window
+            let is_text: BOOL = msg_send![responder, isKindOfClass: class!(NSText)];
+            if is_text == YES {
+                let delegate: cocoa_id = msg_send![responder, delegate];
+                return delegate == target;
+            }
+            false
+        }
+    }
+    fn on_focus_gained(&mut self, callback: Option<OnFocus>) {
+        if let Some(handlers) = unsafe { events::handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_focus_gained = callback;
+        }
+    }
+    fn on_focus_lost(&mut self, callback: Option<OnFocus>) {
+        if let Some(handlers) = unsafe { events::handlers_or_default(self.native_id() as cocoa_id) } {
+            handlers.on_focus_lost = callback;
+        }
+    }
+    fn set_next_focus(&mut self, next: Option<&dyn controls::Member>) {
+        let id = self.native_id() as cocoa_id;
+        unsafe {
+            if !common::is_view_or_window(id) {
+                return;
+            }
+            if let Some(handlers) = events::handlers_or_default(id) {
+                handlers.next_focus = next.map(|next| next.id());
+            }
+            let window: cocoa_id = msg_send![id, window];
+            if let Some(window) = common::member_base_from_cocoa_id_mut(window).map(|base| base.as_member_mut()) {
+                update_key_view_loop(window);
+            }
+        }
+    }
+}
+
+/// Rebuilds the key view loop of the window from its control tree, honoring explicit `set_next_focus` links.
+pub(crate) unsafe fn update_key_view_loop(window: &mut dyn controls::Member) {
+    let window = match window.as_any().downcast_ref::<crate::window::Window>() {
+        Some(window) => window,
+        None => return,
+    };
+    let mut views = Vec::new();
+    if let Some(child) = controls::SingleContainer::child(window) {
+        collect_key_views(child, &mut views);
+    }
+    for (i, &(control, target)) in views.iter().enumerate() {
+        // links to members that are gone or live outside of this window fall back to the child order
+        let next = events::handlers(control)
+            .and_then(|h| h.next_focus)
+            .and_then(|next| controls::Container::find_control(window, types::FindBy::Id(next)))
+            .map(|next| key_view_target(next.native_id() as cocoa_id))
+            .unwrap_or(views[(i + 1) % views.len()].1);
+        let () = msg_send![target, setNextKeyView: next];
+    }
+    let window_id = window.native_id() as cocoa_id;
+    let () = msg_send![window_id, setInitialFirstResponder: views.first().map(|&(_, target)| target).unwrap_or(nil)];
+}
+
+pub(crate) unsafe fn install(class: *const Class) {
+    let class = class as *mut Class;
+    let types = ffi::CString::new(format!("{}{}{}", <BOOL>::encode().as_str(), <*mut Object>::encode().as_str(), <Sel>::encode().as_str())).unwrap();
+    let imp: Imp = mem::transmute(become_first_responder as extern "C" fn(&mut Object, Sel) -> BOOL);
+    runtime::class_addMethod(class, sel!(becomeFirstResponder), imp, types.as_ptr());
+    let imp: Imp = mem::transmute(resign_first_responder as extern "C" fn(&mut Object, Sel) -> BOOL);
+    runtime::class_addMethod(class, sel!(resignFirstResponder), imp, types.as_ptr());
+}
+
+fn collect_key_views(control: &dyn controls::Control, views: &mut Vec<(cocoa_id, cocoa_id)>) {
+    let id = control.native_id() as cocoa_id;
+    views.push((id, unsafe { key_view_target(id) }));
+    if let Some(container) = control.is_container() {
+        if let Some(container) = container.is_single_container() {
+            if let Some(child) = container.child() {
+                collect_key_views(child, views);
+            }
+        } else if let Some(container) = container.is_multi_container() {
+            for i in 0..container.len() {
+                if let Some(child) = container.child_at(i) {
+                    collect_key_views(child, views);
+                }
+            }
+        }
+    }
+}
+
+unsafe fn is_window(id: cocoa_id) -> bool {
+    let is_window: BOOL = msg_send![id, isKindOfClass: class!(NSWindow)];
+    is_window == YES
+}
+
+/// Scrollable controls (lists, trees, tables) take the focus with their document view rather than the scroll view itself.
+unsafe fn key_view_target(id: cocoa_id) -> cocoa_id {
+    let is_scroll: BOOL = msg_send![id, isKindOfClass: class!(NSScrollView)];
+    if is_scroll == YES {
+        let document: cocoa_id = msg_send![id, documentView];
+        if !document.is_null() {
+            return document;
+        }
+    }
+    id
+}
+
+unsafe fn dispatch_focus<F>(this: &mut Object, f: F)
+where
+    F: FnOnce(&mut events::Handlers) -> Option<&mut OnFocus>,
+{
+    let mut id = this as *mut Object;
+    let is_view: BOOL = msg_send![id, isKindOfClass: class!(NSView)];
+    if is_view == YES && events::handlers(id).is_none() {
+        let scroll: cocoa_id = msg_send![id, enclosingScrollView];
+        if !scroll.is_null() && key_view_target(scroll) == id {
+            id = scroll;
+        }
+    }
+    if let Some(callback) = events::handlers(id).and_then(f) {
+        if let Some(member) = events::member_from_cocoa_id_mut(id) {
+            (callback.as_mut())(member);
+        }
+    }
+}
+
+extern "C" fn become_first_responder(this: &mut Object, _: Sel) -> BOOL {
+    unsafe {
+        let became: BOOL = msg_send![super(this, events::superclass(this)), becomeFirstResponder];
+        if became == YES {
+            dispatch_focus(this, |h| h.on_focus_gained.as_mut());
+        }
+        became
+    }
+}
+extern "C" fn resign_first_responder(this: &mut Object, _: Sel) -> BOOL {
+    unsafe {
+        let resigned: BOOL = msg_send![super(this, events::superclass(this)), resignFirstResponder];
+        if resigned == YES {
+            dispatch_focus(this, |h| h.on_focus_lost.as_mut());
+        }
+        resigned
+    }
+}
@@ -1,9 +1,12 @@
 use crate::common::{self, *};
+use crate::style::{HasTextStyle, TextStyle};
 
 pub use std::os::raw::c_char;
 
 const INNER_PADDING_H: i32 = 8; // TODO: WHY???
 const INNER_PADDING_V: i32 = 8; // TODO: WHY???
+const NS_BOX_PRIMARY: NSUInteger = 0;
+const NS_BOX_CUSTOM: NSUInteger = 4;
 
 lazy_static! {
     static ref WINDOW_CLASS: common::RefClass = unsafe {
@@ -22,6 +25,7 @@ pub struct CocoaFrame {
     base: common::CocoaControlBase<Frame>,
     label_padding: (i32, i32),
     child: Option<Box<dyn controls::Control>>,
+    style: TextStyle,
 }
 impl<O: controls::Frame> NewFrameInner<O> for CocoaFrame {
     fn with_uninit(ptr: &mut mem::MaybeUninit<O>) -> Self {
@@ -29,6 +33,7 @@ impl<O: controls::Frame> NewFrameInner<O> for CocoaFrame {
             base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner::<O>),
             label_padding: (0, 0),
             child: None,
+            style: TextStyle::default(),
         };
         let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
         unsafe {
@@ -62,9 +67,54 @@ impl FrameInner for CocoaFrame {
 
 impl CocoaFrame {
     fn measure_label(&mut self) {
-        let label_size = unsafe { common::measure_nsstring(msg_send![self.base.control, title]) };
+        let label_size = unsafe { common::measure_nsstring(msg_send![self.base.control, title], msg_send![self.base.control, titleFont], None) };
         self.label_padding = (label_size.0 as i32, label_size.1 as i32);
     }
+    /// Sends the style fields that differ from `old`, untouched ones keep what the box already has.
+    fn apply_style(&mut self, old: &TextStyle) {
+        unsafe {
+            let control = self.base.control;
+            let title_cell: cocoa_id = msg_send![control, titleCell];
+            if self.style.font != old.font {
+                let () = msg_send![control, setTitleFont: self.style.font.to_native()];
+            }
+            if self.style.text_color != old.text_color {
+                let color: cocoa_id = match self.style.text_color {
+                    Some(color) => color.to_native(),
+                    None => msg_send![class!(NSColor), labelColor],
+                };
+                let () = msg_send![title_cell, setTextColor: color];
+            }
+            if self.style.alignment != old.alignment {
+                let () = msg_send![title_cell, setAlignment: self.style.alignment.to_native()];
+            }
+            if self.style.background_color != old.background_color {
+                match self.style.background_color {
+                    Some(color) => {
+                        // Only custom boxes can be filled
+                        let () = msg_send![control, setBoxType: NS_BOX_CUSTOM];
+                        let () = msg_send![control, setFillColor: color.to_native()];
+                    }
+                    None => {
+                        let () = msg_send![control, setBoxType: NS_BOX_PRIMARY];
+                    }
+                }
+            }
+        }
+        self.measure_label();
+    }
+}
+
+impl HasTextStyle for Frame {
+    fn text_style(&self) -> &TextStyle {
+        &self.inner().inner().inner().inner().inner().style
+    }
+    fn set_text_style(&mut self, style: TextStyle) {
+        let frame = self.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut();
+        let old = mem::replace(&mut frame.style, style);
+        frame.apply_style(&old);
+        frame.base.invalidate();
+    }
 }
 
 impl SingleContainerInner for CocoaFrame {
@@ -1,9 +1,13 @@
 use crate::common::{self, *};
 
+use core_foundation::runloop::kCFRunLoopCommonModes;
+
 lazy_static! {
     static ref WINDOW_CLASS: common::RefClass = unsafe {
         common::register_window_class("PlyguiImage", BASE_CLASS, |decl| {
             decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(drawRect:), draw_rect as extern "C" fn(&mut Object, Sel, NSRect));
+            decl.add_method(sel!(onAnimationTick:), on_animation_tick as extern "C" fn(&mut Object, Sel, cocoa_id));
         })
     };
 }
@@ -11,6 +15,15 @@ lazy_static! {
 const DEFAULT_PADDING: i32 = 6;
 const BASE_CLASS: &str = "NSImageView";
 
+const NS_IMAGE_SCALE_PROPORTIONALLY_DOWN: NSUInteger = 0;
+const NS_IMAGE_SCALE_AXES_INDEPENDENTLY: NSUInteger = 1;
+const NS_IMAGE_SCALE_NONE: NSUInteger = 2;
+const NS_IMAGE_SCALE_PROPORTIONALLY_UP_OR_DOWN: NSUInteger = 3;
+const NS_COMPOSITING_OPERATION_SOURCE_OVER: NSUInteger = 2;
+/// Browsers show GIF frames with shorter delays for 100ms, many files rely on it.
+const MIN_FRAME_DELAY_MS: u32 = 20;
+const DEFAULT_FRAME_DELAY_MS: u32 = 100;
+
 pub type Image = AMember<AControl<AImage<CocoaImage>>>;
 
 #[repr(C)]
@@ -18,24 +31,297 @@ pub struct CocoaImage {
     base: common::CocoaControlBase<Image>,
 
     img: cocoa_id,
+    scaling: ImageScaling,
+
+    animation: Vec<(cocoa_id, u32)>,
+    animation_frame: usize,
+    animation_timer: cocoa_id,
+    looping: bool,
+    on_animation_frame: Option<OnAnimationFrame>,
+}
+
+/// Receives the index of the animation frame just shown.
+pub type OnAnimationFrame = Box<dyn FnMut(&mut dyn controls::Member, usize)>;
+
+/// One frame of an animated image, shown for `delay_ms`.
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub image: image::DynamicImage,
+    pub delay_ms: u32,
+}
+
+impl AnimationFrame {
+    /// Decodes all frames of an animated GIF or APNG. Other formats give a single frame.
+    pub fn decode(bytes: &[u8]) -> image::ImageResult<Vec<AnimationFrame>> {
+        use image::AnimationDecoder;
+        use std::io::Cursor;
+
+        let frames = match image::guess_format(bytes)? {
+            image::ImageFormat::Gif => image::gif::GifDecoder::new(Cursor::new(bytes))?.into_frames().collect_frames()?,
+            image::ImageFormat::Png => image::png::PngDecoder::new(Cursor::new(bytes))?.apng().into_frames().collect_frames()?,
+            _ => vec![image::Frame::new(image::load_from_memory(bytes)?.to_rgba())],
+        };
+        Ok(frames
+            .into_iter()
+            .map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let delay_ms = if denom == 0 { 0 } else { numer / denom };
+                AnimationFrame {
+                    delay_ms: if delay_ms < MIN_FRAME_DELAY_MS { DEFAULT_FRAME_DELAY_MS } else { delay_ms },
+                    image: image::DynamicImage::ImageRgba8(frame.into_buffer()),
+                }
+            })
+            .collect())
+    }
+}
+
+/// How the image is fitted into the control bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageScaling {
+    /// Original size, scaled down to fit the bounds if larger, keeping the aspect ratio.
+    FitDown,
+    /// Original size, centered and clipped.
+    None,
+    /// Stretched to the bounds, ignoring the aspect ratio.
+    Fill,
+    /// Scaled to fit the bounds, keeping the aspect ratio.
+    Fit,
+    /// Scaled to cover the bounds, keeping the aspect ratio, the overflow is clipped.
+    Crop,
+    /// Repeated at original size.
+    Tile,
+}
+
+impl Default for ImageScaling {
+    fn default() -> Self {
+        ImageScaling::FitDown
+    }
+}
+
+impl ImageScaling {
+    fn to_native(&self) -> NSUInteger {
+        match *self {
+            ImageScaling::FitDown => NS_IMAGE_SCALE_PROPORTIONALLY_DOWN,
+            ImageScaling::Fill => NS_IMAGE_SCALE_AXES_INDEPENDENTLY,
+            ImageScaling::Fit => NS_IMAGE_SCALE_PROPORTIONALLY_UP_OR_DOWN,
+            // `Crop` and `Tile` are drawn by `draw_rect`
+            ImageScaling::None | ImageScaling::Crop | ImageScaling::Tile => NS_IMAGE_SCALE_NONE,
+        }
+    }
+}
+
+/// Bitmaps of the same picture at different pixel densities. The system picks the one matching the display,
+/// so the image stays sharp on Retina screens and keeps its size in points everywhere.
+#[derive(Debug, Clone)]
+pub struct ImageSet {
+    reps: Vec<(image::DynamicImage, f32)>,
+}
+
+impl ImageSet {
+    /// A single bitmap drawn at `scale` pixels per point.
+    ///
+    /// Panics if `scale` is not positive.
+    pub fn with_scale(image: image::DynamicImage, scale: f32) -> Self {
+        assert!(scale > 0.0, "image scale must be positive, got {}", scale);
+        ImageSet { reps: vec![(image, scale)] }
+    }
+    /// Regular and Retina variants, the latter twice the size in pixels.
+    pub fn with_1x_2x(image_1x: image::DynamicImage, image_2x: image::DynamicImage) -> Self {
+        ImageSet::with_scale(image_1x, 1.0).and(image_2x, 2.0)
+    }
+    /// Adds a variant for `scale` pixels per point.
+    ///
+    /// Panics if `scale` is not positive.
+    pub fn and(mut self, image: image::DynamicImage, scale: f32) -> Self {
+        assert!(scale > 0.0, "image scale must be positive, got {}", scale);
+        self.reps.push((image, scale));
+        self
+    }
+    /// The variant closest to `scale`, preferring the denser ones.
+    pub fn best_for_scale(&self, scale: f32) -> &image::DynamicImage {
+        self.reps
+            .iter()
+            .min_by(|a, b| {
+                let distance = |s: f32| if s >= scale { s - scale } else { (scale - s) * 2.0 };
+                distance(a.1).partial_cmp(&distance(b.1)).unwrap_or(cmp::Ordering::Equal)
+            })
+            .map(|rep| &rep.0)
+            .expect("ImageSet is never empty")
+    }
+    unsafe fn to_native(&self) -> cocoa_id {
+        let reps: Vec<_> = self.reps.iter().map(|&(ref image, scale)| (image, scale as f64)).collect();
+        common::image_reps_to_native(&reps)
+    }
+}
+
+/// Cocoa-specific `Image` functionality.
+pub trait ImageExt {
+    /// Replaces the image with a multi-resolution one.
+    fn set_image_set(&mut self, set: &ImageSet);
+    fn set_scaling(&mut self, scaling: ImageScaling);
+    fn scaling(&self) -> ImageScaling;
+    /// Pixels per point of the screen the image is shown on.
+    fn backing_scale(&self) -> f32;
+    /// Replaces the image with an animation, paused at its first frame. The control is measured by the first frame.
+    /// An empty `frames` clears the animation and keeps the static image.
+    fn set_animation(&mut self, frames: &[AnimationFrame]);
+    fn play(&mut self);
+    fn pause(&mut self);
+    fn is_playing(&self) -> bool;
+    /// Whether the animation restarts after the last frame, `true` by default. Otherwise it pauses there.
+    fn set_looping(&mut self, looping: bool);
+    fn is_looping(&self) -> bool;
+    /// Index of the shown animation frame.
+    fn animation_frame(&self) -> usize;
+    fn on_animation_frame(&mut self, callback: Option<OnAnimationFrame>);
+}
+
+impl ImageExt for Image {
+    fn set_image_set(&mut self, set: &ImageSet) {
+        let image = self.inner_mut().inner_mut().inner_mut();
+        unsafe {
+            image.install_native(set.to_native());
+        }
+        image.base.invalidate();
+    }
+    fn set_scaling(&mut self, scaling: ImageScaling) {
+        let image = self.inner_mut().inner_mut().inner_mut();
+        if image.scaling != scaling {
+            image.apply_scaling(scaling);
+            image.base.invalidate();
+        }
+    }
+    fn scaling(&self) -> ImageScaling {
+        self.inner().inner().inner().scaling
+    }
+    fn backing_scale(&self) -> f32 {
+        unsafe { common::backing_scale_factor(self.inner().inner().inner().base.control) as f32 }
+    }
+    fn set_animation(&mut self, frames: &[AnimationFrame]) {
+        let image = self.inner_mut().inner_mut().inner_mut();
+        let animation: Vec<_> = frames.iter().map(|frame| (unsafe { common::image_to_native(&frame.image) }, frame.delay_ms)).collect();
+        match animation.first() {
+            Some(&(first, _)) => unsafe {
+                let () = msg_send![first, retain];
+                image.install_native(first);
+            },
+            None => unsafe {
+                image.stop_animation();
+                let () = msg_send![image.base.control, setImage:image.img];
+                return;
+            },
+        }
+        image.animation = animation;
+        image.base.invalidate();
+    }
+    fn play(&mut self) {
+        let image = self.inner_mut().inner_mut().inner_mut();
+        if image.animation_timer.is_null() && !image.animation.is_empty() {
+            if image.animation_frame + 1 == image.animation.len() && !image.looping {
+                image.show_animation_frame(0);
+            }
+            image.schedule_animation_tick();
+        }
+    }
+    fn pause(&mut self) {
+        self.inner_mut().inner_mut().inner_mut().pause_animation();
+    }
+    fn is_playing(&self) -> bool {
+        !self.inner().inner().inner().animation_timer.is_null()
+    }
+    fn set_looping(&mut self, looping: bool) {
+        self.inner_mut().inner_mut().inner_mut().looping = looping;
+    }
+    fn is_looping(&self) -> bool {
+        self.inner().inner().inner().looping
+    }
+    fn animation_frame(&self) -> usize {
+        self.inner().inner().inner().animation_frame
+    }
+    fn on_animation_frame(&mut self, callback: Option<OnAnimationFrame>) {
+        self.inner_mut().inner_mut().inner_mut().on_animation_frame = callback;
+    }
 }
 
 impl CocoaImage {
     fn install_image(&mut self, content: image::DynamicImage) {
         unsafe {
-            self.img = common::image_to_native(&content);
-            let () = msg_send![self.base.control, setImage:self.img];
+            self.install_native(common::image_to_native(&content));
         }
     }
+    unsafe fn install_native(&mut self, img: cocoa_id) {
+        self.stop_animation();
+        self.remove_image();
+        self.img = img;
+        let () = msg_send![self.base.control, setImage:self.img];
+    }
     fn remove_image(&mut self) {
         unsafe {
-            let () = msg_send![self.img, dealloc];
+            let () = msg_send![self.img, release];
+        }
+        self.img = nil;
+    }
+    fn pause_animation(&mut self) {
+        if !self.animation_timer.is_null() {
+            unsafe {
+                let () = msg_send![self.animation_timer, invalidate];
+            }
+            self.animation_timer = nil;
+        }
+    }
+    fn stop_animation(&mut self) {
+        self.pause_animation();
+        for (frame, _) in self.animation.drain(..) {
+            unsafe {
+                let () = msg_send![frame, release];
+            }
+        }
+        self.animation_frame = 0;
+    }
+    fn show_animation_frame(&mut self, index: usize) {
+        self.animation_frame = index;
+        unsafe {
+            let () = msg_send![self.base.control, setImage: self.animation[index].0];
+        }
+    }
+    fn schedule_animation_tick(&mut self) {
+        let interval = self.animation[self.animation_frame].1 as f64 / 1000.0;
+        unsafe {
+            self.animation_timer = msg_send![class!(NSTimer), timerWithTimeInterval:interval target:self.base.control selector:sel!(onAnimationTick:) userInfo:nil repeats:NO];
+            // common modes keep the animation running while menus are open or windows are resized
+            let run_loop: cocoa_id = msg_send![class!(NSRunLoop), mainRunLoop];
+            let () = msg_send![run_loop, addTimer:self.animation_timer forMode:kCFRunLoopCommonModes];
+        }
+    }
+    /// Shows the next frame, returns `false` when a non-looping animation has ended.
+    fn next_animation_frame(&mut self) -> bool {
+        self.animation_timer = nil;
+        if self.animation.is_empty() {
+            return false;
+        }
+        let next = self.animation_frame + 1;
+        if next < self.animation.len() {
+            self.show_animation_frame(next);
+        } else if self.looping {
+            self.show_animation_frame(0);
+        } else {
+            return false;
+        }
+        self.schedule_animation_tick();
+        true
+    }
+    fn apply_scaling(&mut self, scaling: ImageScaling) {
+        self.scaling = scaling;
+        unsafe {
+            let () = msg_send![self.base.control, setImageScaling: scaling.to_native()];
+            let () = msg_send![self.base.control, setNeedsDisplay: YES];
         }
     }
 }
 
 impl Drop for CocoaImage {
     fn drop(&mut self) {
+        self.stop_animation();
         self.remove_image();
     }
 }
@@ -45,6 +331,12 @@ impl<O: controls::Image> NewImageInner<O> for CocoaImage {
         let mut i = CocoaImage {
             base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner::<O>),
             img: nil,
+            scaling: ImageScaling::default(),
+            animation: Vec::new(),
+            animation_frame: 0,
+            animation_timer: nil,
+            looping: true,
+            on_animation_frame: None,
         };
         let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
         unsafe {
@@ -72,16 +364,12 @@ impl ImageInner for CocoaImage {
     }
     fn set_scale(&mut self, _member: &mut MemberBase, policy: types::ImageScalePolicy) {
         if self.scale() != policy {
-            let scale = policy_to_nsscale(policy);
-            unsafe {
-                let () = msg_send![self.base.control, setImageScaling: scale];
-            }
+            self.apply_scaling(policy_to_scaling(policy));
             self.base.invalidate();
         }
     }
     fn scale(&self) -> types::ImageScalePolicy {
-        let scale = unsafe { msg_send![self.base.control, imageScaling] };
-        nsscale_to_policy(scale)
+        scaling_to_policy(self.scaling)
     }
 }
 impl HasImageInner for CocoaImage {
@@ -163,32 +451,17 @@ impl Drawable for CocoaImage {
         control.measured = match control.visibility {
             types::Visibility::Gone => (0, 0),
             _ => unsafe {
-                let mut label_size = (0, 0);
+                // in points, so Retina representations do not double the size
+                let image_size: NSSize = msg_send![self.img, size];
                 let w = match control.layout.width {
                     layout::Size::MatchParent => parent_width as i32,
                     layout::Size::Exact(w) => w as i32,
-                    layout::Size::WrapContent => {
-                        let rep: cocoa_id = msg_send![self.img, representations];
-                        let rep: cocoa_id = msg_send![rep, objectAtIndex:0];
-                        let w: isize = msg_send![rep, pixelsWide];
-                        let h: isize = msg_send![rep, pixelsHigh];
-                        label_size = (w as u16, h as u16);
-                        label_size.0 as i32 + DEFAULT_PADDING + DEFAULT_PADDING
-                    }
+                    layout::Size::WrapContent => image_size.width as i32 + DEFAULT_PADDING + DEFAULT_PADDING,
                 };
                 let h = match control.layout.height {
                     layout::Size::MatchParent => parent_height as i32,
                     layout::Size::Exact(h) => h as i32,
-                    layout::Size::WrapContent => {
-                        if label_size.1 < 1 {
-                            let rep: cocoa_id = msg_send![self.img, representations];
-                            let rep: cocoa_id = msg_send![rep, objectAtIndex:0];
-                            let w: isize = msg_send![rep, pixelsWide];
-                            let h: isize = msg_send![rep, pixelsHigh];
-                            label_size = (w as u16, h as u16);
-                        }
-                        label_size.1 as i32 + DEFAULT_PADDING + DEFAULT_PADDING
-                    }
+                    layout::Size::WrapContent => image_size.height as i32 + DEFAULT_PADDING + DEFAULT_PADDING,
                 };
                 (max(0, w) as u16, max(0, h) as u16)
             },
@@ -200,21 +473,16 @@ impl Drawable for CocoaImage {
     }
 }
 
-fn policy_to_nsscale(i: types::ImageScalePolicy) -> u32 {
-    // TODO NSImageScaling
+fn policy_to_scaling(i: types::ImageScalePolicy) -> ImageScaling {
     match i {
-        types::ImageScalePolicy::CropCenter => 2,
-        types::ImageScalePolicy::FitCenter => 3,
+        types::ImageScalePolicy::CropCenter => ImageScaling::Crop,
+        types::ImageScalePolicy::FitCenter => ImageScaling::Fit,
     }
 }
-fn nsscale_to_policy(i: u32) -> types::ImageScalePolicy {
+fn scaling_to_policy(i: ImageScaling) -> types::ImageScalePolicy {
     match i {
-        3 => types::ImageScalePolicy::FitCenter,
-        2 => types::ImageScalePolicy::CropCenter,
-        _ => {
-            println!("Unknown scale: {}", i);
-            types::ImageScalePolicy::FitCenter
-        }
+        ImageScaling::FitDown | ImageScaling::Fill | ImageScaling::Fit => types::ImageScalePolicy::FitCenter,
+        ImageScaling::None | ImageScaling::Crop | ImageScaling::Tile => types::ImageScalePolicy::CropCenter,
     }
 }
 impl Spawnable for CocoaImage {
@@ -229,6 +497,46 @@ extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
         (b.inner().inner().inner().base.resize_handler)(b2, sel, param)
     }
 }
+extern "C" fn draw_rect(this: &mut Object, _: Sel, dirty: NSRect) {
+    unsafe {
+        let image = common::member_from_cocoa_id_mut::<Image>(this).unwrap().inner().inner().inner();
+        let img = image.img;
+        let bounds: NSRect = msg_send![this, bounds];
+        match image.scaling {
+            ImageScaling::Crop => {
+                let size: NSSize = msg_send![img, size];
+                if size.width > 0.0 && size.height > 0.0 {
+                    let factor = (bounds.size.width / size.width).max(bounds.size.height / size.height);
+                    let size = NSSize::new(size.width * factor, size.height * factor);
+                    let origin = NSPoint::new(bounds.origin.x + (bounds.size.width - size.width) / 2.0, bounds.origin.y + (bounds.size.height - size.height) / 2.0);
+                    let () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+                    let () = msg_send![class!(NSBezierPath), clipRect: bounds];
+                    let () = msg_send![img, drawInRect:NSRect::new(origin, size) fromRect:NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0)) operation:NS_COMPOSITING_OPERATION_SOURCE_OVER fraction:1.0f64 respectFlipped:YES hints:nil];
+                    let () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+                }
+            }
+            ImageScaling::Tile => {
+                let color: cocoa_id = msg_send![class!(NSColor), colorWithPatternImage: img];
+                let () = msg_send![color, setFill];
+                let () = msg_send![class!(NSBezierPath), fillRect: bounds];
+            }
+            ImageScaling::FitDown | ImageScaling::None | ImageScaling::Fill | ImageScaling::Fit => {
+                let () = msg_send![super(this, Class::get(BASE_CLASS).unwrap()), drawRect: dirty];
+            }
+        }
+    }
+}
+extern "C" fn on_animation_tick(this: &mut Object, _: Sel, _: cocoa_id) {
+    let image = unsafe { common::member_from_cocoa_id_mut::<Image>(this) }.unwrap();
+    let image2 = unsafe { common::member_from_cocoa_id_mut::<Image>(this) }.unwrap();
+    let inner = image.inner_mut().inner_mut().inner_mut();
+    if inner.next_animation_frame() {
+        let frame = inner.animation_frame;
+        if let Some(ref mut callback) = inner.on_animation_frame {
+            (callback.as_mut())(image2, frame);
+        }
+    }
+}
 extern "C" fn set_frame_size_inner<O: controls::Image>(this: &mut Image, _: Sel, param: NSSize) {
     unsafe {
         let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
@@ -5,6 +5,8 @@
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
+extern crate derive_builder;
+#[macro_use]
 extern crate plygui_api;
 
 #[macro_use]
@@ -17,14 +19,30 @@ pub use dispatch;
 #[macro_use]
 pub mod common;
 
+mod appearance;
 mod application;
 mod button;
+mod canvas;
+mod clipboard;
+mod combo_box;
+mod dropdown;
+mod events;
+mod focus;
 mod frame;
 mod image;
 mod layout_linear;
 mod message;
+mod notifications;
+mod number_field;
+mod rich_text;
+mod runloop;
+mod scroll_view;
+mod slider;
 mod splitted;
+mod style;
+mod tab_view;
 mod text;
+mod timers;
 mod tray;
 mod window;
 mod progress_bar;
@@ -34,3 +52,25 @@ mod table;
 
 default_markup_register_members!();
 default_pub_use!();
+
+pub use crate::appearance::{Appearance, HasAppearance, OnAppearanceChanged};
+pub use crate::application::{ActivationPolicy, ApplicationExt, ApplicationHandle, OnApplicationAppearanceChanged, OnLifecycle, OnReopen, OnShouldTerminate, TerminationPolicy};
+pub use crate::canvas::{Canvas, CanvasExt, CocoaCanvas, DrawCommand, DrawContext, OnDraw, RecordingContext};
+pub use crate::clipboard::{Clipboard, CocoaClipboard, MemoryClipboard};
+pub use crate::combo_box::{CocoaComboBox, ComboBox, ComboBoxExt};
+pub use crate::dropdown::{CocoaDropdown, Dropdown, DropdownExt, OnSelect};
+pub use crate::events::{HasInputEvents, KeyEvent, Modifiers, MouseButton, MouseEvent, OnKey, OnMouse, OnScroll, ScrollEvent};
+pub use crate::focus::{HasFocus, OnFocus};
+pub use crate::image::{AnimationFrame, ImageExt, ImageScaling, ImageSet, OnAnimationFrame};
+pub use crate::notifications::{CocoaNotifier, Notification, NotificationBuilder, NotificationResponse, Notifier, OnNotification, RecordingNotifier};
+pub use crate::number_field::{CocoaNumberField, NumberField, NumberFieldExt};
+pub use crate::runloop::RunLoopStats;
+pub use crate::scroll_view::{CocoaScrollView, OnScrolled, ScrollDirection, ScrollView, ScrollViewExt};
+pub use crate::slider::{CocoaSlider, OnValueChanged, Slider, SliderExt};
+pub use crate::splitted::{OnSplitterMoved, PaneOptions, SplittedExt};
+pub use crate::style::{Color, Font, FontWeight, HasTextStyle, TextAlignment, TextStyle};
+pub use crate::tab_view::{CocoaTabView, OnTabChanged, TabView, TabViewExt};
+pub use crate::text::{OnLinkClick, TextExt, Truncation};
+pub use crate::timers::{OnTimer, TimerHandle};
+pub use crate::tray::TrayExt;
+pub use crate::window::{TitlebarStyle, WindowExt, WindowFrameState, WindowOptions, WindowOptionsBuilder, WindowOwnership};
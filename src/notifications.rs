@@ -0,0 +1,222 @@
+use crate::common::{self, *};
+
+use std::sync::Mutex;
+
+const NS_USER_NOTIFICATION_ACTIVATION_ACTION_BUTTON_CLICKED: NSInteger = 2;
+const NS_USER_NOTIFICATION_ACTIVATION_ADDITIONAL_ACTION_CLICKED: NSInteger = 4;
+
+/// Local notification posted with `ApplicationExt::post_notification`.
+#[derive(Clone, Builder)]
+#[builder(default)]
+pub struct Notification {
+    #[builder(setter(into))]
+    pub title: String,
+    #[builder(setter(into))]
+    pub body: String,
+    #[builder(setter(strip_option))]
+    pub image: Option<image::DynamicImage>,
+    /// Titles of the action buttons, reported back as `NotificationResponse::Action` with the title index.
+    pub actions: Vec<String>,
+}
+
+impl Default for Notification {
+    fn default() -> Self {
+        Notification {
+            title: String::new(),
+            body: String::new(),
+            image: None,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// What the user did with a delivered notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationResponse {
+    /// The notification itself was clicked.
+    Clicked,
+    /// Action button with the given index in `Notification::actions` was clicked.
+    Action(usize),
+}
+
+/// Callback receiving the identifier returned by `ApplicationExt::post_notification` together with the user response.
+pub type OnNotification = Box<dyn FnMut(&mut dyn controls::Application, &str, NotificationResponse)>;
+
+/// Delivery backend of the application notifications, replaceable with `ApplicationExt::set_notifier`.
+pub trait Notifier {
+    fn post(&mut self, identifier: &str, notification: &Notification);
+    fn remove(&mut self, identifier: &str);
+}
+
+/// Delivers notifications through `NSUserNotificationCenter`, with the application delegate receiving the clicks.
+pub struct CocoaNotifier;
+
+impl Notifier for CocoaNotifier {
+    fn post(&mut self, identifier: &str, notification: &Notification) {
+        unsafe {
+            let center = default_center();
+            if center.is_null() {
+                return;
+            }
+            let n = to_native(identifier, notification);
+            let () = msg_send![center, deliverNotification: n];
+            let () = msg_send![n, release];
+        }
+    }
+    fn remove(&mut self, identifier: &str) {
+        unsafe {
+            let center = default_center();
+            if center.is_null() {
+                return;
+            }
+            let delivered: cocoa_id = msg_send![center, deliveredNotifications];
+            let count: NSUInteger = msg_send![delivered, count];
+            for i in 0..count {
+                let n: cocoa_id = msg_send![delivered, objectAtIndex: i];
+                if common::nsstring_to_string(msg_send![n, identifier]) == identifier {
+                    let () = msg_send![center, removeDeliveredNotification: n];
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Notifier that only remembers what was posted and removed, for tests.
+///
+/// Clones share the records, so a clone kept by the test sees everything posted through the one given to the application.
+#[derive(Clone, Default)]
+pub struct RecordingNotifier {
+    posted: Arc<Mutex<Vec<(String, Notification)>>>,
+    removed: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn posted(&self) -> Vec<(String, Notification)> {
+        self.posted.lock().unwrap().clone()
+    }
+    pub fn removed(&self) -> Vec<String> {
+        self.removed.lock().unwrap().clone()
+    }
+}
+
+impl Notifier for RecordingNotifier {
+    fn post(&mut self, identifier: &str, notification: &Notification) {
+        self.posted.lock().unwrap().push((identifier.to_owned(), notification.clone()));
+    }
+    fn remove(&mut self, identifier: &str) {
+        self.removed.lock().unwrap().push(identifier.to_owned());
+    }
+}
+
+/// Creates a new (retained) `NSUserNotification` for delivery.
+unsafe fn to_native(identifier: &str, notification: &Notification) -> cocoa_id {
+    let n: cocoa_id = msg_send![class!(NSUserNotification), new];
+    let identifier = NSString::alloc(nil).init_str(identifier);
+    let title = NSString::alloc(nil).init_str(&notification.title);
+    let body = NSString::alloc(nil).init_str(&notification.body);
+    let () = msg_send![n, setIdentifier: identifier];
+    let () = msg_send![n, setTitle: title];
+    let () = msg_send![n, setInformativeText: body];
+    let () = msg_send![identifier, release];
+    let () = msg_send![title, release];
+    let () = msg_send![body, release];
+    if let Some(ref image) = notification.image {
+        let image = common::image_to_native(image);
+        let () = msg_send![n, setContentImage: image];
+        let () = msg_send![image, release];
+    }
+    if let Some(first) = notification.actions.first() {
+        let first = NSString::alloc(nil).init_str(first);
+        let () = msg_send![n, setHasActionButton: YES];
+        let () = msg_send![n, setActionButtonTitle: first];
+        let () = msg_send![first, release];
+    } else {
+        let () = msg_send![n, setHasActionButton: NO];
+    }
+    if notification.actions.len() > 1 {
+        let additional: cocoa_id = msg_send![class!(NSMutableArray), array];
+        for (i, title) in notification.actions.iter().enumerate().skip(1) {
+            let identifier = NSString::alloc(nil).init_str(&i.to_string());
+            let title = NSString::alloc(nil).init_str(title);
+            let action: cocoa_id = msg_send![class!(NSUserNotificationAction), actionWithIdentifier:identifier title:title];
+            let () = msg_send![additional, addObject: action];
+            let () = msg_send![identifier, release];
+            let () = msg_send![title, release];
+        }
+        let () = msg_send![n, setAdditionalActions: additional];
+    }
+    n
+}
+
+pub(crate) unsafe fn default_center() -> cocoa_id {
+    // Unbundled executables have no notification center at all
+    msg_send![class!(NSUserNotificationCenter), defaultUserNotificationCenter]
+}
+
+/// Converts the activated `NSUserNotification` into its identifier and the user response.
+pub(crate) unsafe fn response(notification: cocoa_id) -> (String, NotificationResponse) {
+    let identifier = common::nsstring_to_string(msg_send![notification, identifier]);
+    let activation: NSInteger = msg_send![notification, activationType];
+    let response = match activation {
+        NS_USER_NOTIFICATION_ACTIVATION_ACTION_BUTTON_CLICKED => NotificationResponse::Action(0),
+        NS_USER_NOTIFICATION_ACTIVATION_ADDITIONAL_ACTION_CLICKED => {
+            let action: cocoa_id = msg_send![notification, additionalActivationAction];
+            let action: cocoa_id = if action.is_null() { nil } else { msg_send![action, identifier] };
+            common::nsstring_to_string(action).parse().map(NotificationResponse::Action).unwrap_or(NotificationResponse::Clicked)
+        }
+        _ => NotificationResponse::Clicked,
+    };
+    (identifier, response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_notifier_clones_share_records() {
+        let recorder = RecordingNotifier::new();
+        let mut notifier: Box<dyn Notifier> = Box::new(recorder.clone());
+        notifier.post("1", &NotificationBuilder::default().title("Title").body("Body").build().unwrap());
+        notifier.post("2", &Notification::default());
+        notifier.remove("1");
+
+        let posted = recorder.posted();
+        assert_eq!(posted.len(), 2);
+        assert_eq!(posted[0].0, "1");
+        assert_eq!(posted[0].1.title, "Title");
+        assert_eq!(posted[0].1.body, "Body");
+        assert_eq!(posted[1].0, "2");
+        assert_eq!(recorder.removed(), vec!["1".to_owned()]);
+    }
+
+    #[test]
+    fn native_notification_carries_texts_and_actions() {
+        let notification = NotificationBuilder::default()
+            .title("Title")
+            .body("Body")
+            .actions(vec!["Open".to_owned(), "Later".to_owned(), "Never".to_owned()])
+            .build()
+            .unwrap();
+        unsafe {
+            let n = to_native("id", &notification);
+            assert_eq!(common::nsstring_to_string(msg_send![n, identifier]), "id");
+            assert_eq!(common::nsstring_to_string(msg_send![n, title]), "Title");
+            assert_eq!(common::nsstring_to_string(msg_send![n, informativeText]), "Body");
+            let has_action_button: BOOL = msg_send![n, hasActionButton];
+            assert_eq!(has_action_button, YES);
+            assert_eq!(common::nsstring_to_string(msg_send![n, actionButtonTitle]), "Open");
+            let additional: cocoa_id = msg_send![n, additionalActions];
+            let count: NSUInteger = msg_send![additional, count];
+            assert_eq!(count, 2);
+            let last: cocoa_id = msg_send![additional, objectAtIndex: 1];
+            assert_eq!(common::nsstring_to_string(msg_send![last, identifier]), "2");
+            assert_eq!(common::nsstring_to_string(msg_send![last, title]), "Never");
+            let () = msg_send![n, release];
+        }
+    }
+}
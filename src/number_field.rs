@@ -0,0 +1,372 @@
+use crate::common::{self, *};
+use crate::slider::{self, OnValueChanged};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiNumberField", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(onTextCommitted:), text_committed as extern "C" fn(&mut Object, Sel, cocoa_id));
+            decl.add_method(sel!(onStepperChanged:), stepper_changed as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSView";
+const DEFAULT_FIELD_WIDTH: i32 = 80;
+const STEPPER_GAP: f64 = 2.0;
+const MAX_FRACTION_DIGITS: usize = 6;
+const NS_NUMBER_FORMATTER_DECIMAL_STYLE: NSUInteger = 1;
+const NS_TEXT_ALIGNMENT_RIGHT: NSUInteger = 1;
+
+pub type NumberField = AMember<AControl<CocoaNumberField>>;
+
+/// Numeric text field with a stepper. The stepper holds the value, the field shows it and accepts typed values on Enter.
+#[repr(C)]
+pub struct CocoaNumberField {
+    base: common::CocoaControlBase<NumberField>,
+    field: cocoa_id,
+    stepper: cocoa_id,
+    on_value_changed: Option<OnValueChanged>,
+}
+
+pub trait NumberFieldExt {
+    fn set_range(&mut self, min: f64, max: f64);
+    fn range(&self) -> (f64, f64);
+    /// Stepper increment, typed values are snapped to its multiples from the minimum. Must be positive.
+    fn set_step(&mut self, step: f64);
+    fn step(&self) -> f64;
+    fn set_value(&mut self, value: f64);
+    fn value(&self) -> f64;
+    fn on_value_changed(&mut self, callback: Option<OnValueChanged>);
+}
+
+impl NumberFieldExt for NumberField {
+    fn set_range(&mut self, min: f64, max: f64) {
+        self.inner_mut().inner_mut().set_range(min, max)
+    }
+    fn range(&self) -> (f64, f64) {
+        self.inner().inner().range()
+    }
+    fn set_step(&mut self, step: f64) {
+        self.inner_mut().inner_mut().set_step(step)
+    }
+    fn step(&self) -> f64 {
+        self.inner().inner().step()
+    }
+    fn set_value(&mut self, value: f64) {
+        self.inner_mut().inner_mut().set_value(value)
+    }
+    fn value(&self) -> f64 {
+        self.inner().inner().value()
+    }
+    fn on_value_changed(&mut self, callback: Option<OnValueChanged>) {
+        self.inner_mut().inner_mut().on_value_changed = callback;
+    }
+}
+
+impl CocoaNumberField {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<NumberField>) -> Self {
+        let base = common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner);
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *base.control).set_ivar(common::IVAR, selfptr);
+
+            let rect = NSRect::new(NSPoint::new(0f64, 0f64), NSSize::new(0f64, 0f64));
+            let formatter: cocoa_id = msg_send![class!(NSNumberFormatter), new];
+            let () = msg_send![formatter, setNumberStyle: NS_NUMBER_FORMATTER_DECIMAL_STYLE];
+            let () = msg_send![formatter, setUsesGroupingSeparator: NO];
+            let field: cocoa_id = msg_send![class!(NSTextField), alloc];
+            let field: cocoa_id = msg_send![field, initWithFrame: rect];
+            let () = msg_send![field, setFormatter: formatter];
+            let () = msg_send![formatter, release];
+            let () = msg_send![field, setAlignment: NS_TEXT_ALIGNMENT_RIGHT];
+            let () = msg_send![field, setTarget: base.control];
+            let () = msg_send![field, setAction: sel!(onTextCommitted:)];
+
+            let stepper: cocoa_id = msg_send![class!(NSStepper), alloc];
+            let stepper: cocoa_id = msg_send![stepper, initWithFrame: rect];
+            let () = msg_send![stepper, setValueWraps: NO];
+            let () = msg_send![stepper, setAutorepeat: YES];
+            let () = msg_send![stepper, setTarget: base.control];
+            let () = msg_send![stepper, setAction: sel!(onStepperChanged:)];
+
+            let () = msg_send![base.control, addSubview: field];
+            let () = msg_send![base.control, addSubview: stepper];
+            CocoaNumberField {
+                base,
+                field,
+                stepper,
+                on_value_changed: None,
+            }
+        }
+    }
+    pub fn with_range(min: f64, max: f64) -> Box<NumberField> {
+        let mut b: Box<mem::MaybeUninit<NumberField>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                Self::with_uninit(b.as_mut()),
+            ),
+        );
+        let mut ab = unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        };
+        ab.set_step(1.0);
+        ab.set_range(min, max);
+        ab.set_value(min);
+        ab
+    }
+    fn range(&self) -> (f64, f64) {
+        unsafe { (msg_send![self.stepper, minValue], msg_send![self.stepper, maxValue]) }
+    }
+    fn set_range(&mut self, min: f64, max: f64) {
+        let (min, max) = (min.min(max), max.max(min));
+        unsafe {
+            let () = msg_send![self.stepper, setMinValue: min];
+            let () = msg_send![self.stepper, setMaxValue: max];
+            let formatter: cocoa_id = msg_send![self.field, formatter];
+            let min: cocoa_id = msg_send![class!(NSNumber), numberWithDouble: min];
+            let max: cocoa_id = msg_send![class!(NSNumber), numberWithDouble: max];
+            let () = msg_send![formatter, setMinimum: min];
+            let () = msg_send![formatter, setMaximum: max];
+        }
+        let value = self.value();
+        self.set_value(value);
+    }
+    fn step(&self) -> f64 {
+        unsafe { msg_send![self.stepper, increment] }
+    }
+    fn set_step(&mut self, step: f64) {
+        if step <= 0.0 {
+            return;
+        }
+        unsafe {
+            let () = msg_send![self.stepper, setIncrement: step];
+            let formatter: cocoa_id = msg_send![self.field, formatter];
+            let () = msg_send![formatter, setMaximumFractionDigits: fraction_digits(step) as NSUInteger];
+        }
+        let value = self.value();
+        self.set_value(value);
+    }
+    fn value(&self) -> f64 {
+        unsafe { msg_send![self.stepper, doubleValue] }
+    }
+    fn set_value(&mut self, value: f64) {
+        let value = slider::snap(value, self.range(), self.step());
+        unsafe {
+            let () = msg_send![self.stepper, setDoubleValue: value];
+            let () = msg_send![self.field, setDoubleValue: value];
+        }
+    }
+    fn fitting_sizes(&self) -> (NSSize, NSSize) {
+        unsafe { (msg_send![self.field, fittingSize], msg_send![self.stepper, fittingSize]) }
+    }
+    fn layout_parts(&mut self, (width, height): (u16, u16)) {
+        let (field, stepper) = self.fitting_sizes();
+        let (width, height) = (width as f64, height as f64);
+        let field_width = (width - stepper.width - STEPPER_GAP).max(0.0);
+        unsafe {
+            let () = msg_send![self.field, setFrame: NSRect::new(NSPoint::new(0.0, ((height - field.height) / 2.0).max(0.0)), NSSize::new(field_width, field.height.min(height)))];
+            let () = msg_send![self.stepper, setFrame: NSRect::new(NSPoint::new(field_width + STEPPER_GAP, ((height - stepper.height) / 2.0).max(0.0)), NSSize::new(stepper.width, stepper.height.min(height)))];
+        }
+    }
+}
+
+/// Decimal digits needed to show multiples of `step`.
+fn fraction_digits(step: f64) -> usize {
+    (0..MAX_FRACTION_DIGITS).find(|digits| (step * 10f64.powi(*digits as i32)).fract().abs() < 1e-9).unwrap_or(MAX_FRACTION_DIGITS)
+}
+
+impl Drop for CocoaNumberField {
+    fn drop(&mut self) {
+        unsafe {
+            let () = msg_send![self.field, release];
+            let () = msg_send![self.stepper, release];
+        }
+    }
+}
+
+impl ControlInner for CocoaNumberField {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _x: i32, _y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        self.base.invalidate();
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, NumberField, ["NumberField"]);
+
+        if let Some(step) = common::markup_number(markup, "step") {
+            self.set_step(step);
+        }
+        let (min, max) = self.range();
+        self.set_range(common::markup_number(markup, "min").unwrap_or(min), common::markup_number(markup, "max").unwrap_or(max));
+        let value = common::markup_number(markup, "value").unwrap_or_else(|| self.value());
+        self.set_value(value);
+    }
+}
+
+impl HasNativeIdInner for CocoaNumberField {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaNumberField {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaNumberField {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaNumberField {}
+
+impl HasLayoutInner for CocoaNumberField {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drawable for CocoaNumberField {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+        self.layout_parts(control.measured);
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                let (field, stepper) = self.fitting_sizes();
+                let w = match control.layout.width {
+                    layout::Size::MatchParent => parent_width as i32,
+                    layout::Size::Exact(w) => w as i32,
+                    layout::Size::WrapContent => DEFAULT_FIELD_WIDTH + (STEPPER_GAP + stepper.width) as i32,
+                };
+                let h = match control.layout.height {
+                    layout::Size::MatchParent => parent_height as i32,
+                    layout::Size::Exact(h) => h as i32,
+                    layout::Size::WrapContent => field.height.max(stepper.height) as i32,
+                };
+                (cmp::max(0, w) as u16, cmp::max(0, h) as u16)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Spawnable for CocoaNumberField {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_range(0.0, 100.0)
+    }
+}
+
+extern "C" fn text_committed(this: &mut Object, _: Sel, _: cocoa_id) {
+    let field = unsafe { common::member_from_cocoa_id_mut::<NumberField>(this) }.unwrap();
+    let inner = field.inner_mut().inner_mut();
+    // the stepper still holds the previous value
+    let (typed, old): (f64, f64) = unsafe { (msg_send![inner.field, doubleValue], msg_send![inner.stepper, doubleValue]) };
+    value_changed(this, typed, old);
+}
+extern "C" fn stepper_changed(this: &mut Object, _: Sel, _: cocoa_id) {
+    let field = unsafe { common::member_from_cocoa_id_mut::<NumberField>(this) }.unwrap();
+    let inner = field.inner_mut().inner_mut();
+    // the field still shows the previous value
+    let (stepped, old): (f64, f64) = unsafe { (msg_send![inner.stepper, doubleValue], msg_send![inner.field, doubleValue]) };
+    value_changed(this, stepped, old);
+}
+fn value_changed(this: &mut Object, value: f64, old: f64) {
+    let field = unsafe { common::member_from_cocoa_id_mut::<NumberField>(this) }.unwrap();
+    let field2 = unsafe { common::member_from_cocoa_id_mut::<NumberField>(this) }.unwrap();
+    let inner = field.inner_mut().inner_mut();
+    inner.set_value(value);
+    let value = inner.value();
+    if (old - value).abs() > f64::EPSILON {
+        if let Some(ref mut callback) = inner.on_value_changed {
+            (callback.as_mut())(field2, value);
+        }
+    }
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<NumberField>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<NumberField>(this).unwrap();
+        (b.inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut NumberField, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<NumberField>(param.width as u16, param.height as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn shows_the_fraction_digits_of_the_step() {
+        assert_eq!(fraction_digits(1.0), 0);
+        assert_eq!(fraction_digits(5.0), 0);
+        assert_eq!(fraction_digits(0.1), 1);
+        assert_eq!(fraction_digits(0.25), 2);
+        assert_eq!(fraction_digits(0.05), 2);
+        assert_eq!(fraction_digits(1e-9), MAX_FRACTION_DIGITS);
+    }
+
+    #[test]
+    fn typed_values_are_snapped_to_the_step() {
+        let mut field = CocoaNumberField::with_range(0.0, 10.0);
+        field.set_step(0.25);
+        field.set_value(1.1);
+        assert_eq!(field.value(), 1.0);
+
+        let reported = Rc::new(Cell::new(0.0));
+        let reported2 = reported.clone();
+        field.on_value_changed(Some(Box::new(move |_, value| reported2.set(value))));
+        let inner = field.inner().inner();
+        let (control, text) = (inner.base.control, inner.field);
+        unsafe {
+            // what typing a value and pressing Enter does
+            let () = msg_send![text, setDoubleValue: 1.3f64];
+            let () = msg_send![control, onTextCommitted: text];
+            let shown: f64 = msg_send![text, doubleValue];
+            assert_eq!(shown, 1.25);
+        }
+        assert_eq!(reported.get(), 1.25);
+        assert_eq!(field.value(), 1.25);
+    }
+}
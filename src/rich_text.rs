@@ -0,0 +1,224 @@
+//! Small markup subset for `TextExt::set_markup`:
+//!
+//! * `<b>bold</b>`, `<i>italic</i>`, `<tt>monospace</tt>`
+//! * `<color=#rrggbb>colored</color>`, also `#rrggbbaa`
+//! * `<a=https://example.com>link</a>`
+//! * `&lt;`, `&gt;` and `&amp;` for the literal characters
+//!
+//! Tags nest, unknown tags and unmatched closing tags are kept as plain text.
+
+use crate::common::*;
+use crate::style::{self, Color, TextStyle};
+
+const NS_ITALIC_FONT_MASK: NSUInteger = 1;
+const NS_BOLD_FONT_MASK: NSUInteger = 2;
+const NS_LINK_ATTRIBUTE_NAME: &str = "NSLink";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct RichSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub monospace: bool,
+    pub color: Option<Color>,
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tag {
+    Bold,
+    Italic,
+    Monospace,
+    Color(Color),
+    Link(String),
+}
+
+impl Tag {
+    fn parse(tag: &str) -> Option<Tag> {
+        match tag {
+            "b" => Some(Tag::Bold),
+            "i" => Some(Tag::Italic),
+            "tt" => Some(Tag::Monospace),
+            _ if tag.starts_with("color=") => parse_color(&tag["color=".len()..]).map(Tag::Color),
+            _ if tag.starts_with("a=") && tag.len() > 2 => Some(Tag::Link(tag["a=".len()..].to_owned())),
+            _ => None,
+        }
+    }
+    fn closes(&self, name: &str) -> bool {
+        match *self {
+            Tag::Bold => name == "b",
+            Tag::Italic => name == "i",
+            Tag::Monospace => name == "tt",
+            Tag::Color(_) => name == "color",
+            Tag::Link(_) => name == "a",
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        6 => Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?)),
+        8 => Some(Color::rgba(channel(0)?, channel(2)?, channel(4)?, channel(6)?)),
+        _ => None,
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+pub(crate) fn parse(markup: &str) -> Vec<RichSpan> {
+    let mut spans = Vec::new();
+    let mut stack: Vec<Tag> = Vec::new();
+    let mut text = String::new();
+    let mut rest = markup;
+
+    let flush = |text: &mut String, stack: &[Tag], spans: &mut Vec<RichSpan>| {
+        if text.is_empty() {
+            return;
+        }
+        let mut span = RichSpan {
+            text: unescape(text),
+            ..Default::default()
+        };
+        for tag in stack {
+            match *tag {
+                Tag::Bold => span.bold = true,
+                Tag::Italic => span.italic = true,
+                Tag::Monospace => span.monospace = true,
+                Tag::Color(color) => span.color = Some(color),
+                Tag::Link(ref link) => span.link = Some(link.clone()),
+            }
+        }
+        spans.push(span);
+        text.clear();
+    };
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => {
+                text.push_str(&rest[start..]);
+                rest = "";
+                break;
+            }
+        };
+        let tag = &rest[start + 1..end];
+        if let Some(name) = tag.strip_prefix('/') {
+            if stack.last().map(|open| open.closes(name)).unwrap_or(false) {
+                flush(&mut text, &stack, &mut spans);
+                stack.pop();
+            } else {
+                text.push_str(&rest[start..=end]);
+            }
+        } else if let Some(open) = Tag::parse(tag) {
+            flush(&mut text, &stack, &mut spans);
+            stack.push(open);
+        } else {
+            text.push_str(&rest[start..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    flush(&mut text, &stack, &mut spans);
+    spans
+}
+
+/// Renders the spans into a new (retained) `NSAttributedString`, with `style` as the base look.
+pub(crate) unsafe fn to_native(spans: &[RichSpan], style: &TextStyle) -> cocoa_id {
+    let base_font = style.font.to_native();
+    let base_size: f64 = msg_send![base_font, pointSize];
+    let base_color: cocoa_id = match style.text_color {
+        Some(color) => color.to_native(),
+        None => msg_send![class!(NSColor), textColor],
+    };
+    let manager: cocoa_id = msg_send![class!(NSFontManager), sharedFontManager];
+    let result: cocoa_id = msg_send![class!(NSMutableAttributedString), new];
+    for span in spans {
+        let mut font: cocoa_id = if span.monospace { msg_send![class!(NSFont), userFixedPitchFontOfSize: base_size] } else { base_font };
+        if span.bold {
+            font = msg_send![manager, convertFont:font toHaveTrait:NS_BOLD_FONT_MASK];
+        }
+        if span.italic {
+            font = msg_send![manager, convertFont:font toHaveTrait:NS_ITALIC_FONT_MASK];
+        }
+        let color = span.color.map(|color| color.to_native()).unwrap_or(base_color);
+        let attributes = style::attributes(font, color, Some(style.alignment));
+        if let Some(ref link) = span.link {
+            let key = NSString::alloc(nil).init_str(NS_LINK_ATTRIBUTE_NAME);
+            let link = NSString::alloc(nil).init_str(link);
+            let () = msg_send![attributes, setObject:link forKey:key];
+            let () = msg_send![link, release];
+            let () = msg_send![key, release];
+        }
+        let text = NSString::alloc(nil).init_str(&span.text);
+        let part: cocoa_id = msg_send![class!(NSAttributedString), alloc];
+        let part: cocoa_id = msg_send![part, initWithString:text attributes:attributes];
+        let () = msg_send![result, appendAttributedString: part];
+        let () = msg_send![part, release];
+        let () = msg_send![text, release];
+    }
+    result
+}
+
+/// Whether any of the spans is a link, which requires the text to be selectable to receive clicks.
+pub(crate) fn has_links(spans: &[RichSpan]) -> bool {
+    spans.iter().any(|span| span.link.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str) -> RichSpan {
+        RichSpan {
+            text: text.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_nested_tags() {
+        let spans = parse("plain <b>bold <i>both</i></b> <color=#ff000080>red</color>");
+        assert_eq!(
+            spans,
+            vec![
+                span("plain "),
+                RichSpan { bold: true, ..span("bold ") },
+                RichSpan { bold: true, italic: true, ..span("both") },
+                span(" "),
+                RichSpan {
+                    color: Some(Color::rgba(0xff, 0, 0, 0x80)),
+                    ..span("red")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_links_and_entities() {
+        let spans = parse("<a=https://example.com>a &lt;link&gt;</a> &amp; <tt>code</tt>");
+        assert_eq!(
+            spans,
+            vec![
+                RichSpan {
+                    link: Some("https://example.com".to_owned()),
+                    ..span("a <link>")
+                },
+                span(" & "),
+                RichSpan { monospace: true, ..span("code") },
+            ]
+        );
+        assert!(has_links(&spans));
+    }
+
+    #[test]
+    fn keeps_unknown_and_unmatched_tags() {
+        assert_eq!(parse("<u>x</u></b><color=red>y"), vec![span("<u>x</u></b><color=red>y")]);
+        assert_eq!(parse("a < b"), vec![span("a < b")]);
+        assert!(!has_links(&parse("<b>no links</b>")));
+    }
+}
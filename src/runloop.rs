@@ -0,0 +1,230 @@
+use crate::application::Application;
+use crate::common::*;
+
+use core_foundation::base::{kCFAllocatorDefault, TCFType};
+use core_foundation::runloop::{
+    kCFRunLoopAfterWaiting, kCFRunLoopBeforeWaiting, kCFRunLoopCommonModes, CFRunLoop, CFRunLoopActivity, CFRunLoopObserver, CFRunLoopObserverContext, CFRunLoopObserverCreate, CFRunLoopObserverInvalidate, CFRunLoopObserverRef, CFRunLoopSource,
+    CFRunLoopSourceContext, CFRunLoopSourceCreate, CFRunLoopSourceInvalidate, CFRunLoopSourceSignal, CFRunLoopWakeUp,
+};
+use dispatch::Queue;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Closure posted through `ApplicationHandle::post`.
+pub(crate) type Posted = Box<dyn FnOnce(&mut dyn controls::Application) + Send>;
+
+/// Main run loop counters, see `ApplicationExt::run_loop_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunLoopStats {
+    /// Number of times the main thread woke up from waiting for events.
+    pub wakeups: u64,
+    /// Time spent sleeping while waiting for events.
+    pub idle: Duration,
+    /// Time spent handling events, callbacks and drawing.
+    pub busy: Duration,
+    /// Frame callbacks (`controls::Application::on_frame`) executed.
+    pub frame_callbacks: u64,
+    /// Closures posted through `ApplicationHandle` executed.
+    pub dispatched: u64,
+    /// Sum of the delays between posting a closure through `ApplicationHandle` and its execution.
+    pub dispatch_latency: Duration,
+    pub max_dispatch_latency: Duration,
+}
+
+impl RunLoopStats {
+    /// Share of the measured time the main thread was asleep, `1.0` for a fully idle application.
+    pub fn idle_ratio(&self) -> f64 {
+        let total = self.idle + self.busy;
+        if total == Duration::from_secs(0) {
+            1.0
+        } else {
+            self.idle.as_secs_f64() / total.as_secs_f64()
+        }
+    }
+    pub fn average_dispatch_latency(&self) -> Duration {
+        if self.dispatched == 0 {
+            Duration::from_secs(0)
+        } else {
+            self.dispatch_latency / self.dispatched as u32
+        }
+    }
+    pub(crate) fn record_dispatch(&mut self, latency: Duration) {
+        self.dispatched += 1;
+        self.dispatch_latency += latency;
+        self.max_dispatch_latency = cmp::max(self.max_dispatch_latency, latency);
+    }
+}
+
+/// Runs queued work from a run loop source instead of polling. The source is signalled and the main run loop woken up
+/// whenever something is posted, frame callbacks queued from the main thread itself are also drained right before the loop goes to sleep.
+/// Callbacks asking to be called again signal the source once more `frame_sleep` milliseconds later, the loop stays asleep otherwise.
+pub(crate) struct RunLoopIntegration {
+    observer: CFRunLoopObserver,
+    mailbox: Arc<Mailbox>,
+}
+
+/// Thread-safe end of the integration shared with every `ApplicationHandle`.
+pub(crate) struct Mailbox {
+    posted: Mutex<Vec<(Instant, Posted)>>,
+    source: CFRunLoopSource,
+    main: CFRunLoop,
+    /// Whether a delayed wake-up for re-queued frame callbacks is already on its way.
+    delayed: AtomicBool,
+}
+
+// CFRunLoopSourceSignal and CFRunLoopWakeUp are safe to call from any thread
+unsafe impl Send for Mailbox {}
+unsafe impl Sync for Mailbox {}
+
+impl Mailbox {
+    pub(crate) fn post(&self, f: Posted) {
+        self.posted.lock().unwrap().push((Instant::now(), f));
+        self.wake();
+    }
+    fn wake(&self) {
+        unsafe {
+            CFRunLoopSourceSignal(self.source.as_concrete_TypeRef());
+            CFRunLoopWakeUp(self.main.as_concrete_TypeRef());
+        }
+    }
+}
+
+impl RunLoopIntegration {
+    pub(crate) fn install(app: *mut Application) -> RunLoopIntegration {
+        let info = app as *mut c_void;
+        let mut observer_context = CFRunLoopObserverContext {
+            version: 0,
+            info: info,
+            retain: None,
+            release: None,
+            copyDescription: None,
+        };
+        let mut source_context = CFRunLoopSourceContext {
+            version: 0,
+            info: info,
+            retain: None,
+            release: None,
+            copyDescription: None,
+            equal: None,
+            hash: None,
+            schedule: None,
+            cancel: None,
+            perform: perform,
+        };
+        unsafe {
+            let observer = CFRunLoopObserverCreate(kCFAllocatorDefault, kCFRunLoopBeforeWaiting | kCFRunLoopAfterWaiting, 1, 0, observe, &mut observer_context);
+            let observer = CFRunLoopObserver::wrap_under_create_rule(observer);
+            let source = CFRunLoopSource::wrap_under_create_rule(CFRunLoopSourceCreate(kCFAllocatorDefault, 0, &mut source_context));
+            let main = CFRunLoop::get_main();
+            main.add_observer(&observer, kCFRunLoopCommonModes);
+            main.add_source(&source, kCFRunLoopCommonModes);
+            // Whatever was queued before the run loop started
+            CFRunLoopSourceSignal(source.as_concrete_TypeRef());
+            RunLoopIntegration {
+                observer: observer,
+                mailbox: Arc::new(Mailbox {
+                    posted: Mutex::new(Vec::new()),
+                    source: source,
+                    main: main,
+                    delayed: AtomicBool::new(false),
+                }),
+            }
+        }
+    }
+    pub(crate) fn mailbox(&self) -> Arc<Mailbox> {
+        self.mailbox.clone()
+    }
+}
+
+impl Drop for RunLoopIntegration {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopObserverInvalidate(self.observer.as_concrete_TypeRef());
+            CFRunLoopSourceInvalidate(self.mailbox.source.as_concrete_TypeRef());
+        }
+    }
+}
+
+/// Runs the posted closures and up to `defaults::MAX_FRAME_CALLBACKS` queued frame callbacks, returns `true` if any of the latter is to be called again.
+fn drain(app: &mut Application) -> bool {
+    let app_ptr = app as *mut Application;
+    let posted = match app.inner().run_loop {
+        Some(ref run_loop) => mem::replace(&mut *run_loop.mailbox.posted.lock().unwrap(), Vec::new()),
+        None => Vec::new(),
+    };
+    for (at, f) in posted {
+        app.inner_mut().stats.record_dispatch(Instant::now() - at);
+        let app2: &mut Application = unsafe { &mut *app_ptr };
+        f(app2);
+    }
+    let mut frame_callbacks = 0;
+    let mut requeued = false;
+    {
+        let b = &mut app.base;
+        while frame_callbacks < defaults::MAX_FRAME_CALLBACKS {
+            match b.queue().try_recv() {
+                Ok(mut cmd) => {
+                    let app2: &mut Application = unsafe { &mut *app_ptr };
+                    if (cmd.as_mut())(app2) {
+                        let _ = b.sender().send(cmd);
+                        requeued = true;
+                    }
+                    frame_callbacks += 1;
+                }
+                Err(e) => match e {
+                    mpsc::TryRecvError::Empty => break,
+                    mpsc::TryRecvError::Disconnected => unreachable!(),
+                },
+            }
+        }
+    }
+    app.inner_mut().stats.frame_callbacks += frame_callbacks as u64;
+    requeued || frame_callbacks >= defaults::MAX_FRAME_CALLBACKS
+}
+
+fn schedule(app: &mut Application, pending: bool) {
+    if !pending {
+        return;
+    }
+    let sleep = app.inner().sleep;
+    if let Some(ref run_loop) = app.inner().run_loop {
+        let mailbox = run_loop.mailbox();
+        if sleep == 0 {
+            mailbox.wake();
+        } else if !mailbox.delayed.swap(true, Ordering::SeqCst) {
+            Queue::main().exec_after(Duration::from_millis(sleep as u64), move || {
+                mailbox.delayed.store(false, Ordering::SeqCst);
+                mailbox.wake();
+            });
+        }
+    }
+}
+
+extern "C" fn observe(_: CFRunLoopObserverRef, activity: CFRunLoopActivity, info: *mut c_void) {
+    let app = unsafe { &mut *(info as *mut Application) };
+    let now = Instant::now();
+    if activity == kCFRunLoopBeforeWaiting {
+        let pending = drain(app);
+        schedule(app, pending);
+        let inner = app.inner_mut();
+        if let Some(awake) = inner.awake_since.take() {
+            inner.stats.busy += Instant::now() - awake;
+        }
+        inner.asleep_since = Some(Instant::now());
+    } else if activity == kCFRunLoopAfterWaiting {
+        let inner = app.inner_mut();
+        if let Some(asleep) = inner.asleep_since.take() {
+            inner.stats.idle += now - asleep;
+        }
+        inner.stats.wakeups += 1;
+        inner.awake_since = Some(now);
+    }
+}
+
+extern "C" fn perform(info: *const c_void) {
+    let app = unsafe { &mut *(info as *mut Application) };
+    let pending = drain(app);
+    schedule(app, pending);
+}
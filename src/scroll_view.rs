@@ -0,0 +1,426 @@
+use crate::common::{self, *};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiScrollView", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(onScrollBoundsChanged:), on_scroll_bounds_changed as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+    static ref DOCUMENT_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiScrollDocument", "NSView", |decl| {
+            decl.add_method(sel!(isFlipped), is_flipped as extern "C" fn(&mut Object, Sel) -> BOOL);
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSScrollView";
+const NS_VIEW_BOUNDS_DID_CHANGE_NOTIFICATION: &str = "NSViewBoundsDidChangeNotification";
+
+pub type ScrollView = AMember<AControl<AContainer<ASingleContainer<CocoaScrollView>>>>;
+
+/// Receives the new scroll offset.
+pub type OnScrolled = Box<dyn FnMut(&mut dyn controls::Member, (f64, f64))>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl ScrollDirection {
+    fn is_horizontal(&self) -> bool {
+        *self != ScrollDirection::Vertical
+    }
+    fn is_vertical(&self) -> bool {
+        *self != ScrollDirection::Horizontal
+    }
+}
+
+#[repr(C)]
+pub struct CocoaScrollView {
+    base: common::CocoaControlBase<ScrollView>,
+    document: cocoa_id,
+    document_size: (u16, u16),
+    direction: ScrollDirection,
+    child: Option<Box<dyn controls::Control>>,
+    on_scrolled: Option<OnScrolled>,
+}
+
+/// Scrollable single container, the child is measured against the visible area and may overflow it in the scrolling directions.
+pub trait ScrollViewExt {
+    fn set_scroll_direction(&mut self, direction: ScrollDirection);
+    fn scroll_direction(&self) -> ScrollDirection;
+    /// Offset of the visible area from the top-left corner of the content.
+    fn scroll_offset(&self) -> (f64, f64);
+    /// Scrolls to the offset, clamped to the content.
+    fn scroll_to(&mut self, x: f64, y: f64);
+    /// Scrolls the least needed to make the control with this id visible, `false` if it is not inside the scroll view.
+    fn reveal(&mut self, id: ids::Id) -> bool;
+    fn on_scrolled(&mut self, callback: Option<OnScrolled>);
+}
+
+impl ScrollViewExt for ScrollView {
+    fn set_scroll_direction(&mut self, direction: ScrollDirection) {
+        let scroll = self.inner_mut().inner_mut().inner_mut().inner_mut();
+        scroll.direction = direction;
+        scroll.apply_direction();
+        scroll.base.invalidate();
+    }
+    fn scroll_direction(&self) -> ScrollDirection {
+        self.inner().inner().inner().inner().direction
+    }
+    fn scroll_offset(&self) -> (f64, f64) {
+        self.inner().inner().inner().inner().offset()
+    }
+    fn scroll_to(&mut self, x: f64, y: f64) {
+        let scroll = self.inner_mut().inner_mut().inner_mut().inner_mut();
+        unsafe {
+            let clip: cocoa_id = msg_send![scroll.base.control, contentView];
+            let visible: NSRect = msg_send![clip, bounds];
+            let max_x = (scroll.document_size.0 as f64 - visible.size.width).max(0.0);
+            let max_y = (scroll.document_size.1 as f64 - visible.size.height).max(0.0);
+            let () = msg_send![clip, scrollToPoint: NSPoint::new(x.max(0.0).min(max_x), y.max(0.0).min(max_y))];
+            let () = msg_send![scroll.base.control, reflectScrolledClipView: clip];
+        }
+    }
+    fn reveal(&mut self, id: ids::Id) -> bool {
+        let target = match controls::Container::find_control(self, types::FindBy::Id(id)) {
+            Some(control) => control.native_id() as cocoa_id,
+            None => return false,
+        };
+        unsafe {
+            let bounds: NSRect = msg_send![target, bounds];
+            let scrolled: BOOL = msg_send![target, scrollRectToVisible: bounds];
+            scrolled == YES
+        }
+    }
+    fn on_scrolled(&mut self, callback: Option<OnScrolled>) {
+        self.inner_mut().inner_mut().inner_mut().inner_mut().on_scrolled = callback;
+    }
+}
+
+impl CocoaScrollView {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<ScrollView>) -> Self {
+        let sc = CocoaScrollView {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            document: unsafe {
+                let document: cocoa_id = msg_send![DOCUMENT_CLASS.0, alloc];
+                msg_send![document, initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0))]
+            },
+            document_size: (0, 0),
+            direction: ScrollDirection::Vertical,
+            child: None,
+            on_scrolled: None,
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *sc.base.control).set_ivar(common::IVAR, selfptr);
+            let () = msg_send![sc.base.control, setDocumentView: sc.document];
+            let () = msg_send![sc.base.control, setDrawsBackground: NO];
+
+            let clip: cocoa_id = msg_send![sc.base.control, contentView];
+            let () = msg_send![clip, setPostsBoundsChangedNotifications: YES];
+            let center: cocoa_id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let name = NSString::alloc(nil).init_str(NS_VIEW_BOUNDS_DID_CHANGE_NOTIFICATION);
+            let () = msg_send![center, addObserver:sc.base.control selector:sel!(onScrollBoundsChanged:) name:name object:clip];
+            let () = msg_send![name, release];
+        }
+        sc
+    }
+    pub fn with_direction(direction: ScrollDirection) -> Box<ScrollView> {
+        let mut b: Box<mem::MaybeUninit<ScrollView>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                AContainer::with_inner(
+                    ASingleContainer::with_inner(
+                        Self::with_uninit(b.as_mut()),
+                    ),
+                ),
+            ),
+        );
+        let mut ab = unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        };
+        ScrollViewExt::set_scroll_direction(ab.as_mut(), direction);
+        ab
+    }
+    fn apply_direction(&mut self) {
+        unsafe {
+            let () = msg_send![self.base.control, setHasHorizontalScroller: if self.direction.is_horizontal() { YES } else { NO }];
+            let () = msg_send![self.base.control, setHasVerticalScroller: if self.direction.is_vertical() { YES } else { NO }];
+        }
+    }
+    fn offset(&self) -> (f64, f64) {
+        unsafe {
+            let clip: cocoa_id = msg_send![self.base.control, contentView];
+            let visible: NSRect = msg_send![clip, bounds];
+            (visible.origin.x, visible.origin.y)
+        }
+    }
+    fn attach_child(&mut self) {
+        if let Some(ref mut child) = self.child {
+            let scroll2 = unsafe { common::member_from_cocoa_id_mut::<ScrollView>(self.base.control).unwrap() };
+            let (pw, ph) = scroll2.inner().base.measured;
+            child.on_added_to_container(scroll2, 0, 0, pw, ph);
+        }
+    }
+}
+
+impl Drop for CocoaScrollView {
+    fn drop(&mut self) {
+        unsafe {
+            let center: cocoa_id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let () = msg_send![center, removeObserver: self.base.control];
+            let () = msg_send![self.document, release];
+        }
+    }
+}
+
+impl SingleContainerInner for CocoaScrollView {
+    fn set_child(&mut self, _: &mut MemberBase, child: Option<Box<dyn controls::Control>>) -> Option<Box<dyn controls::Control>> {
+        let mut old = self.child.take();
+        self.child = child;
+        if let Some(ref mut child) = self.child {
+            unsafe {
+                let child_id = child.native_id() as cocoa_id;
+                (&mut *child_id).set_ivar(common::IVAR_PARENT, self.base.control as *mut c_void);
+                let () = msg_send![self.document, addSubview: child_id];
+            }
+            if self.base.root().is_some() {
+                self.attach_child();
+            }
+        }
+        if let Some(ref mut old) = old {
+            unsafe {
+                let child_id = old.native_id() as cocoa_id;
+                *(&mut *child_id).get_mut_ivar::<*mut c_void>(common::IVAR_PARENT) = ptr::null_mut();
+                let () = msg_send![child_id, removeFromSuperview];
+                let scroll2 = common::member_from_cocoa_id_mut::<ScrollView>(self.base.control).unwrap();
+                if self.base.root().is_some() {
+                    old.on_removed_from_container(scroll2);
+                }
+            }
+        }
+        self.base.invalidate();
+        old
+    }
+    fn child(&self) -> Option<&dyn controls::Control> {
+        self.child.as_ref().map(|c| c.as_ref())
+    }
+    fn child_mut(&mut self) -> Option<&mut dyn controls::Control> {
+        if let Some(child) = self.child.as_mut() {
+            Some(child.as_mut())
+        } else {
+            None
+        }
+    }
+}
+
+impl ContainerInner for CocoaScrollView {
+    fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
+        if let Some(child) = self.child.as_mut() {
+            match arg {
+                types::FindBy::Id(id) => {
+                    if child.as_member_mut().id() == id {
+                        return Some(child.as_mut());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container_mut() {
+                c.find_control_mut(arg)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
+        if let Some(child) = self.child.as_ref() {
+            match arg {
+                types::FindBy::Id(id) => {
+                    if child.as_member().id() == id {
+                        return Some(child.as_ref());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container() {
+                c.find_control(arg)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl ControlInner for CocoaScrollView {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _x: i32, _y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+
+        if let Some(ref mut child) = self.child {
+            let scroll2 = unsafe { common::member_from_cocoa_id_mut::<ScrollView>(self.base.control).unwrap() };
+            let (pw, ph) = control.measured;
+            child.on_added_to_container(scroll2, 0, 0, pw, ph);
+        }
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        let scroll2 = unsafe { common::member_from_cocoa_id_mut::<ScrollView>(self.base.control).unwrap() };
+        if let Some(ref mut child) = self.child {
+            child.on_removed_from_container(scroll2);
+        }
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, ScrollView, ["ScrollView"]);
+        fill_from_markup_child!(self, base, markup, registry);
+    }
+}
+
+impl HasNativeIdInner for CocoaScrollView {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaScrollView {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaScrollView {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaScrollView {}
+
+impl HasLayoutInner for CocoaScrollView {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drawable for CocoaScrollView {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+        unsafe {
+            let () = msg_send![self.document, setFrameSize: NSSize::new(self.document_size.0 as f64, self.document_size.1 as f64)];
+        }
+        if let Some(ref mut child) = self.child {
+            child.draw(Some((0, 0)));
+        }
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        let visible_width = match control.layout.width {
+            layout::Size::Exact(w) => w,
+            _ => parent_width,
+        };
+        let visible_height = match control.layout.height {
+            layout::Size::Exact(h) => h,
+            _ => parent_height,
+        };
+        let (cw, ch, _) = if let Some(ref mut child) = self.child {
+            child.measure(visible_width, visible_height)
+        } else {
+            (0, 0, false)
+        };
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                let w = match control.layout.width {
+                    layout::Size::Exact(w) => w,
+                    layout::Size::MatchParent => parent_width,
+                    layout::Size::WrapContent => cmp::min(cw, parent_width),
+                };
+                let h = match control.layout.height {
+                    layout::Size::Exact(h) => h,
+                    layout::Size::MatchParent => parent_height,
+                    layout::Size::WrapContent => cmp::min(ch, parent_height),
+                };
+                (w, h)
+            }
+        };
+        self.document_size = (
+            if self.direction.is_horizontal() { cmp::max(cw, control.measured.0) } else { control.measured.0 },
+            if self.direction.is_vertical() { cmp::max(ch, control.measured.1) } else { control.measured.1 },
+        );
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Spawnable for CocoaScrollView {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_direction(ScrollDirection::Vertical)
+    }
+}
+
+extern "C" fn on_scroll_bounds_changed(this: &mut Object, _: Sel, _: cocoa_id) {
+    let scroll = unsafe { common::member_from_cocoa_id_mut::<ScrollView>(this) }.unwrap();
+    let scroll2 = unsafe { common::member_from_cocoa_id_mut::<ScrollView>(this) }.unwrap();
+    let inner = scroll.inner_mut().inner_mut().inner_mut().inner_mut();
+    let offset = inner.offset();
+    if let Some(ref mut callback) = inner.on_scrolled {
+        (callback.as_mut())(scroll2, offset);
+    }
+}
+extern "C" fn is_flipped(_: &mut Object, _: Sel) -> BOOL {
+    YES
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<ScrollView>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<ScrollView>(this).unwrap();
+        (b.inner().inner().inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut ScrollView, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<ScrollView>(param.width as u16, param.height as u16);
+    }
+}
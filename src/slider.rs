@@ -0,0 +1,344 @@
+use crate::common::{self, *};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiSlider", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(onValueChanged:), value_changed as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSSlider";
+const DEFAULT_LENGTH: i32 = 160;
+
+pub type Slider = AMember<AControl<CocoaSlider>>;
+
+/// Receives the new value, called on user input only.
+pub type OnValueChanged = Box<dyn FnMut(&mut dyn controls::Member, f64)>;
+
+#[repr(C)]
+pub struct CocoaSlider {
+    base: common::CocoaControlBase<Slider>,
+    step: f64,
+    on_value_changed: Option<OnValueChanged>,
+}
+
+pub trait SliderExt {
+    fn set_range(&mut self, min: f64, max: f64);
+    fn range(&self) -> (f64, f64);
+    /// Values are snapped to multiples of `step` from the minimum, `0.0` makes the slider continuous.
+    fn set_step(&mut self, step: f64);
+    fn step(&self) -> f64;
+    fn set_value(&mut self, value: f64);
+    fn value(&self) -> f64;
+    /// Number of tick marks drawn along the track, `0` hides them.
+    fn set_tick_marks(&mut self, count: usize);
+    fn tick_marks(&self) -> usize;
+    fn set_orientation(&mut self, orientation: layout::Orientation);
+    fn orientation(&self) -> layout::Orientation;
+    fn on_value_changed(&mut self, callback: Option<OnValueChanged>);
+}
+
+impl SliderExt for Slider {
+    fn set_range(&mut self, min: f64, max: f64) {
+        let slider = self.inner_mut().inner_mut();
+        unsafe {
+            let () = msg_send![slider.base.control, setMinValue: min.min(max)];
+            let () = msg_send![slider.base.control, setMaxValue: max.max(min)];
+        }
+        let value = slider.value();
+        slider.set_value(value);
+    }
+    fn range(&self) -> (f64, f64) {
+        self.inner().inner().range()
+    }
+    fn set_step(&mut self, step: f64) {
+        let slider = self.inner_mut().inner_mut();
+        slider.step = step.max(0.0);
+        let value = slider.value();
+        slider.set_value(value);
+    }
+    fn step(&self) -> f64 {
+        self.inner().inner().step
+    }
+    fn set_value(&mut self, value: f64) {
+        self.inner_mut().inner_mut().set_value(value)
+    }
+    fn value(&self) -> f64 {
+        self.inner().inner().value()
+    }
+    fn set_tick_marks(&mut self, count: usize) {
+        unsafe {
+            let () = msg_send![self.inner().inner().base.control, setNumberOfTickMarks: count as NSInteger];
+        }
+    }
+    fn tick_marks(&self) -> usize {
+        let count: NSInteger = unsafe { msg_send![self.inner().inner().base.control, numberOfTickMarks] };
+        count as usize
+    }
+    fn set_orientation(&mut self, orientation: layout::Orientation) {
+        let slider = self.inner_mut().inner_mut();
+        let vertical = match orientation {
+            layout::Orientation::Horizontal => NO,
+            layout::Orientation::Vertical => YES,
+        };
+        unsafe {
+            let () = msg_send![slider.base.control, setVertical: vertical];
+        }
+        slider.base.invalidate();
+    }
+    fn orientation(&self) -> layout::Orientation {
+        self.inner().inner().orientation()
+    }
+    fn on_value_changed(&mut self, callback: Option<OnValueChanged>) {
+        self.inner_mut().inner_mut().on_value_changed = callback;
+    }
+}
+
+impl CocoaSlider {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<Slider>) -> Self {
+        let s = CocoaSlider {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            step: 0.0,
+            on_value_changed: None,
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *s.base.control).set_ivar(common::IVAR, selfptr);
+            let () = msg_send![s.base.control, setTarget: s.base.control];
+            let () = msg_send![s.base.control, setAction: sel!(onValueChanged:)];
+            let () = msg_send![s.base.control, setContinuous: YES];
+        }
+        s
+    }
+    pub fn with_range(min: f64, max: f64) -> Box<Slider> {
+        let mut b: Box<mem::MaybeUninit<Slider>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                Self::with_uninit(b.as_mut()),
+            ),
+        );
+        let mut ab = unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        };
+        ab.set_range(min, max);
+        ab.set_value(min);
+        ab
+    }
+    fn range(&self) -> (f64, f64) {
+        unsafe { (msg_send![self.base.control, minValue], msg_send![self.base.control, maxValue]) }
+    }
+    fn value(&self) -> f64 {
+        unsafe { msg_send![self.base.control, doubleValue] }
+    }
+    fn set_value(&mut self, value: f64) {
+        let value = snap(value, self.range(), self.step);
+        unsafe {
+            let () = msg_send![self.base.control, setDoubleValue: value];
+        }
+    }
+    fn orientation(&self) -> layout::Orientation {
+        let vertical: BOOL = unsafe { msg_send![self.base.control, isVertical] };
+        if vertical == YES {
+            layout::Orientation::Vertical
+        } else {
+            layout::Orientation::Horizontal
+        }
+    }
+}
+
+/// Clamps `value` to `(min, max)` and rounds it to the closest multiple of `step` from `min`.
+pub(crate) fn snap(value: f64, (min, max): (f64, f64), step: f64) -> f64 {
+    let value = if step > 0.0 { min + ((value - min) / step).round() * step } else { value };
+    value.max(min).min(max)
+}
+
+impl ControlInner for CocoaSlider {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _x: i32, _y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        self.base.invalidate();
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, Slider, ["Slider"]);
+
+        let (min, max) = self.range();
+        let (min, max) = (common::markup_number(markup, "min").unwrap_or(min), common::markup_number(markup, "max").unwrap_or(max));
+        unsafe {
+            let () = msg_send![self.base.control, setMinValue: min.min(max)];
+            let () = msg_send![self.base.control, setMaxValue: max.max(min)];
+            if let Some(ticks) = common::markup_number(markup, "ticks") {
+                let () = msg_send![self.base.control, setNumberOfTickMarks: ticks as NSInteger];
+            }
+            if let Some(orientation) = markup.attributes.get("orientation") {
+                let vertical = if orientation.as_attribute() == "vertical" { YES } else { NO };
+                let () = msg_send![self.base.control, setVertical: vertical];
+            }
+        }
+        if let Some(step) = common::markup_number(markup, "step") {
+            self.step = step.max(0.0);
+        }
+        let value = common::markup_number(markup, "value").unwrap_or_else(|| self.value());
+        self.set_value(value);
+    }
+}
+
+impl HasNativeIdInner for CocoaSlider {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaSlider {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaSlider {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaSlider {}
+
+impl HasLayoutInner for CocoaSlider {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drawable for CocoaSlider {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                // the track has no natural length, only its thickness is provided by AppKit
+                let fitting: NSSize = unsafe { msg_send![self.base.control, fittingSize] };
+                let (wrap_w, wrap_h) = match self.orientation() {
+                    layout::Orientation::Horizontal => (DEFAULT_LENGTH, fitting.height as i32),
+                    layout::Orientation::Vertical => (fitting.width as i32, DEFAULT_LENGTH),
+                };
+                let w = match control.layout.width {
+                    layout::Size::MatchParent => parent_width as i32,
+                    layout::Size::Exact(w) => w as i32,
+                    layout::Size::WrapContent => wrap_w + DEFAULT_PADDING + DEFAULT_PADDING,
+                };
+                let h = match control.layout.height {
+                    layout::Size::MatchParent => parent_height as i32,
+                    layout::Size::Exact(h) => h as i32,
+                    layout::Size::WrapContent => wrap_h + DEFAULT_PADDING + DEFAULT_PADDING,
+                };
+                (cmp::max(0, w) as u16, cmp::max(0, h) as u16)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Spawnable for CocoaSlider {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::with_range(0.0, 1.0)
+    }
+}
+
+extern "C" fn value_changed(this: &mut Object, _: Sel, _: cocoa_id) {
+    let slider = unsafe { common::member_from_cocoa_id_mut::<Slider>(this) }.unwrap();
+    let slider2 = unsafe { common::member_from_cocoa_id_mut::<Slider>(this) }.unwrap();
+    let inner = slider.inner_mut().inner_mut();
+    let value = inner.value();
+    inner.set_value(value);
+    let value = inner.value();
+    if let Some(ref mut callback) = inner.on_value_changed {
+        (callback.as_mut())(slider2, value);
+    }
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<Slider>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<Slider>(this).unwrap();
+        (b.inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut Slider, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<Slider>(param.width as u16, param.height as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn snaps_to_steps_from_the_minimum() {
+        assert_eq!(snap(3.6, (0.0, 10.0), 2.5), 2.5);
+        assert_eq!(snap(4.2, (1.0, 10.0), 2.0), 5.0);
+        assert_eq!(snap(11.0, (0.0, 10.0), 2.5), 10.0);
+        assert_eq!(snap(-3.0, (0.0, 10.0), 2.5), 0.0);
+    }
+
+    #[test]
+    fn clamps_without_a_step() {
+        assert_eq!(snap(3.6, (0.0, 10.0), 0.0), 3.6);
+        assert_eq!(snap(12.0, (0.0, 10.0), 0.0), 10.0);
+    }
+
+    #[test]
+    fn slider_reports_snapped_values() {
+        let mut slider = CocoaSlider::with_range(0.0, 10.0);
+        slider.set_step(2.5);
+        slider.set_value(3.6);
+        assert_eq!(slider.value(), 2.5);
+
+        let reported = Rc::new(Cell::new(0.0));
+        let reported2 = reported.clone();
+        slider.on_value_changed(Some(Box::new(move |_, value| reported2.set(value))));
+        let control = slider.inner().inner().base.control;
+        unsafe {
+            // what a drag of the knob does
+            let () = msg_send![control, setDoubleValue: 7.4f64];
+            let () = msg_send![control, onValueChanged: control];
+            let native: f64 = msg_send![control, doubleValue];
+            assert_eq!(native, 7.5);
+        }
+        assert_eq!(reported.get(), 7.5);
+        assert_eq!(slider.value(), 7.5);
+    }
+}
@@ -12,69 +12,235 @@ pub type Splitted = AMember<AControl<AContainer<AMultiContainer<ASplitted<CocoaS
 
 const BASE_CLASS: &str = "NSSplitView";
 const PADDING: i32 = 4; // TODO WHY??
+const NS_WINDOW_BELOW: NSInteger = -1;
+
+/// Receives the divider index and its new position, as a fraction of the `Splitted` length.
+pub type OnSplitterMoved = Box<dyn FnMut(&mut dyn controls::Member, usize, f32)>;
+
+/// Size limits of a pane along the split, in points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PaneOptions {
+    pub min: u16,
+    pub max: Option<u16>,
+    /// Whether double-clicking an adjacent divider collapses the pane.
+    pub collapsible: bool,
+}
+
+struct Pane {
+    child: Box<dyn controls::Control>,
+    options: PaneOptions,
+    collapsed: bool,
+}
+
+impl Pane {
+    fn new(child: Box<dyn controls::Control>) -> Self {
+        Pane {
+            child,
+            options: PaneOptions::default(),
+            collapsed: false,
+        }
+    }
+}
 
 #[repr(C)]
 pub struct CocoaSplitted {
     base: common::CocoaControlBase<Splitted>,
-    splitter: f32,
-    first: Box<dyn controls::Control>,
-    second: Box<dyn controls::Control>,
+    /// Divider positions as fractions of the length, one less than panes. Collapsed panes keep theirs to be restored.
+    positions: Vec<f32>,
+    panes: Vec<Pane>,
+    on_splitter_moved: Option<OnSplitterMoved>,
+}
+
+/// Any number of panes for `Splitted`, added through `controls::MultiContainer`. `first` / `second` and `splitter`
+/// refer to the first two panes and the divider between them, at least two panes are always kept.
+pub trait SplittedExt {
+    fn set_pane_options(&mut self, index: usize, options: PaneOptions);
+    fn pane_options(&self, index: usize) -> Option<PaneOptions>;
+    fn set_pane_collapsed(&mut self, index: usize, collapsed: bool);
+    fn is_pane_collapsed(&self, index: usize) -> bool;
+    /// Divider positions as fractions of the length, e.g. to store them between runs and restore with `set_divider_positions`.
+    fn divider_positions(&self) -> Vec<f32>;
+    /// Extra positions are ignored, missing ones keep their current value.
+    fn set_divider_positions(&mut self, positions: &[f32]);
+    /// Called when the user drags a divider.
+    fn on_splitter_moved(&mut self, callback: Option<OnSplitterMoved>);
+}
+
+impl SplittedExt for Splitted {
+    fn set_pane_options(&mut self, index: usize, options: PaneOptions) {
+        let (m, c, sp) = self.as_control_parts_mut();
+        let sp = sp.inner_mut().inner_mut().inner_mut();
+        if let Some(pane) = sp.panes.get_mut(index) {
+            pane.options = options;
+            if !options.collapsible {
+                pane.collapsed = false;
+            }
+            sp.constrain_positions(sp.length(m, sp.base.size(c)));
+            sp.update_splitter(m, c);
+            sp.base.invalidate();
+        }
+    }
+    fn pane_options(&self, index: usize) -> Option<PaneOptions> {
+        self.inner().inner().inner().inner().inner().panes.get(index).map(|pane| pane.options)
+    }
+    fn set_pane_collapsed(&mut self, index: usize, collapsed: bool) {
+        let sp = self.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut();
+        if let Some(pane) = sp.panes.get_mut(index) {
+            if pane.collapsed != collapsed && (pane.options.collapsible || !collapsed) {
+                pane.collapsed = collapsed;
+                sp.base.invalidate();
+            }
+        }
+    }
+    fn is_pane_collapsed(&self, index: usize) -> bool {
+        self.inner().inner().inner().inner().inner().panes.get(index).map(|pane| pane.collapsed).unwrap_or(false)
+    }
+    fn divider_positions(&self) -> Vec<f32> {
+        self.inner().inner().inner().inner().inner().positions.clone()
+    }
+    fn set_divider_positions(&mut self, positions: &[f32]) {
+        let (m, c, sp) = self.as_control_parts_mut();
+        let sp = sp.inner_mut().inner_mut().inner_mut();
+        for (current, new) in sp.positions.iter_mut().zip(positions) {
+            *current = new.max(0.0).min(1.0);
+        }
+        // keep them ordered
+        for i in 1..sp.positions.len() {
+            sp.positions[i] = sp.positions[i].max(sp.positions[i - 1]);
+        }
+        sp.constrain_positions(sp.length(m, sp.base.size(c)));
+        sp.update_splitter(m, c);
+        sp.base.invalidate();
+    }
+    fn on_splitter_moved(&mut self, callback: Option<OnSplitterMoved>) {
+        self.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().on_splitter_moved = callback;
+    }
 }
 
 impl CocoaSplitted {
-    fn children_sizes(&self, member: &MemberBase, control: &ControlBase) -> (u16, u16) {
-        let (w, h) = self.base.size(control);
-        let splitter: f32 = unsafe { msg_send![self.base.control, dividerThickness] };
-        let target = match self.orientation(member) {
+    fn thickness(&self) -> f32 {
+        unsafe { msg_send![self.base.control, dividerThickness] }
+    }
+    fn length(&self, member: &MemberBase, (w, h): (u16, u16)) -> u16 {
+        match self.orientation(member) {
             layout::Orientation::Horizontal => w,
             layout::Orientation::Vertical => h,
-        };
-        (
-            utils::coord_to_size((target as f32 * self.splitter) as i32 - (splitter as i32) - PADDING),
-            utils::coord_to_size((target as f32 * (1.0 - self.splitter)) as i32 - (splitter as i32) - PADDING),
-        )
+        }
+    }
+    /// Pane edges in points along the split, collapsed panes leave their space to the next visible pane,
+    /// or to the last visible one if none follows.
+    fn edges(&self, length: u16) -> Vec<f32> {
+        let length = length as f32;
+        let mut edges = Vec::with_capacity(self.panes.len() + 1);
+        edges.push(0.0);
+        edges.extend(self.positions.iter().map(|position| position * length));
+        edges.push(length);
+        let last_visible = self.panes.iter().rposition(|pane| !pane.collapsed).unwrap_or(0);
+        for (i, pane) in self.panes.iter().enumerate() {
+            if pane.collapsed {
+                if i < last_visible {
+                    edges[i + 1] = edges[i];
+                } else {
+                    edges[i] = length;
+                }
+            }
+        }
+        edges
+    }
+    /// Range of the divider between `divider` and the next pane allowed by both pane limits, in the split view coordinates.
+    fn divider_range(&self, length: u16, divider: usize) -> (f64, f64) {
+        let thickness = self.thickness() as f64;
+        let edges = self.edges(length);
+        let start = if divider > 0 { edges[divider] as f64 + thickness / 2.0 } else { 0.0 };
+        let end = if divider + 2 < edges.len() - 1 { edges[divider + 2] as f64 - thickness / 2.0 } else { length as f64 };
+        let (before, after) = (self.panes[divider].options, self.panes[divider + 1].options);
+        let min = (start + before.min as f64).max(after.max.map(|max| end - thickness - max as f64).unwrap_or(0.0));
+        let max = (end - thickness - after.min as f64).min(before.max.map(|max| start + max as f64).unwrap_or(length as f64));
+        (min, max)
+    }
+    /// Moves the stored positions into the ranges allowed by the pane limits for the given length, the first pane wins if they cannot be met.
+    fn constrain_positions(&mut self, length: u16) {
+        if length < 1 {
+            return;
+        }
+        let thickness = self.thickness() as f64;
+        for divider in 0..self.positions.len() {
+            if self.panes[divider].collapsed || self.panes[divider + 1].collapsed {
+                continue;
+            }
+            let (min, max) = self.divider_range(length, divider);
+            let coordinate = (self.positions[divider] as f64 * length as f64 - thickness / 2.0).min(max).max(min);
+            self.positions[divider] = ((coordinate + thickness / 2.0) / length as f64) as f32;
+        }
+    }
+    /// Start and size of every pane along the split.
+    fn pane_spans(&self, length: u16) -> Vec<(i32, u16)> {
+        let gap = self.thickness() / 2.0 + PADDING as f32;
+        let edges = self.edges(length);
+        let last = self.panes.len() - 1;
+        self.panes
+            .iter()
+            .enumerate()
+            .map(|(i, pane)| {
+                let start = edges[i] + if i > 0 { gap } else { 0.0 };
+                let end = edges[i + 1] - if i < last { gap } else { 0.0 };
+                if pane.collapsed {
+                    return (start as i32, 0);
+                }
+                let mut size = cmp::max(pane.options.min as i32, (end - start) as i32);
+                if let Some(max) = pane.options.max {
+                    size = cmp::min(size, max as i32);
+                }
+                (start as i32, utils::coord_to_size(size))
+            })
+            .collect()
     }
     fn update_splitter(&mut self, member: &MemberBase, control: &ControlBase) {
-        let orientation = self.orientation(member);
-        let () = match orientation {
-            layout::Orientation::Horizontal => unsafe { msg_send![self.base.control, setPosition:(control.measured.0 as f32 * self.splitter) ofDividerAtIndex:0] },
-            layout::Orientation::Vertical => unsafe { msg_send![self.base.control, setPosition:(control.measured.1 as f32 * self.splitter) ofDividerAtIndex:0] },
-        };
+        let edges = self.edges(self.length(member, control.measured));
+        let thickness = self.thickness();
+        for i in 0..self.positions.len() {
+            let () = unsafe { msg_send![self.base.control, setPosition:(edges[i + 1] - thickness / 2.0) ofDividerAtIndex:i as NSInteger] };
+        }
+    }
+    /// Pane next to the divider a double click collapses, or restores if already collapsed.
+    fn collapse_target(&self, divider: usize) -> Option<usize> {
+        if self.panes[divider].collapsed || self.panes[divider + 1].collapsed {
+            Some(if self.panes[divider].collapsed { divider } else { divider + 1 })
+        } else if self.panes[divider].options.collapsible {
+            Some(divider)
+        } else if self.panes[divider + 1].options.collapsible {
+            Some(divider + 1)
+        } else {
+            None
+        }
     }
     fn draw_children(&mut self, member: &MemberBase, control: &ControlBase) {
-        let splitter: f32 = unsafe { msg_send![self.base.control, dividerThickness] };
         let o = self.orientation(member);
-        let (first, _) = self.children_sizes(member, control);
-        let (pw, ph) = control.measured;
-        let (fw, fh) = self.first.size();
-        let (sw, sh) = self.second.size();
-        // TODO why children of splitted are drawn from top rather from bottom?
-        match o {
-            layout::Orientation::Horizontal => {
-                self.first.draw(Some((0, ph as i32 - fh as i32)));
-                self.second.draw(Some((first as i32 + splitter as i32 + PADDING + PADDING, ph as i32 - sh as i32)));
-            }
-            layout::Orientation::Vertical => {
-                self.first.draw(Some((pw as i32 - fw as i32, 0)));
-                self.second.draw(Some((pw as i32 - sw as i32, first as i32 + splitter as i32 + PADDING + PADDING)));
+        let spans = self.pane_spans(self.length(member, self.base.size(control)));
+        let (pw, _) = control.measured;
+        for (pane, (start, _)) in self.panes.iter_mut().zip(spans) {
+            let (cw, _) = pane.child.size();
+            match o {
+                layout::Orientation::Horizontal => pane.child.draw(Some((start, 0))),
+                layout::Orientation::Vertical => pane.child.draw(Some((pw as i32 - cw as i32, start))),
             }
         }
     }
     fn update_children_layout(&mut self, member: &MemberBase, control: &ControlBase) -> (u16, u16) {
         let orientation = self.orientation(member);
-        let (first_size, second_size) = self.children_sizes(member, control);
+        let spans = self.pane_spans(self.length(member, self.base.size(control)));
         let (width, height) = control.measured;
         let mut w = 0;
         let mut h = 0;
-        for (size, child) in [(first_size, self.first.as_mut()), (second_size, self.second.as_mut())].iter_mut() {
+        for (pane, (_, size)) in self.panes.iter_mut().zip(spans) {
             match orientation {
                 layout::Orientation::Horizontal => {
-                    let (cw, ch, _) = child.measure(cmp::max(0, *size) as u16, cmp::max(0, height as i32 - DEFAULT_PADDING - DEFAULT_PADDING) as u16);
+                    let (cw, ch, _) = pane.child.measure(size, cmp::max(0, height as i32 - DEFAULT_PADDING - DEFAULT_PADDING) as u16);
                     w += cw;
                     h = cmp::max(h, ch);
                 }
                 layout::Orientation::Vertical => {
-                    let (cw, ch, _) = child.measure(cmp::max(0, width as i32 - DEFAULT_PADDING - DEFAULT_PADDING) as u16, cmp::max(0, *size) as u16);
+                    let (cw, ch, _) = pane.child.measure(cmp::max(0, width as i32 - DEFAULT_PADDING - DEFAULT_PADDING) as u16, size);
                     w = cmp::max(w, cw);
                     h += ch;
                 }
@@ -82,15 +248,27 @@ impl CocoaSplitted {
         }
         (w, h)
     }
+    fn add_pane_to_container(&mut self, index: usize) {
+        let self2 = unsafe { common::member_from_cocoa_id_mut::<Splitted>(self.base.control).unwrap() };
+        let (m, c, _) = self2.as_control_parts_mut();
+        let o = self.orientation(m);
+        let (start, size) = self.pane_spans(self.length(m, c.measured))[index];
+        let (pw, ph) = c.measured;
+        let self2 = unsafe { common::member_from_cocoa_id_mut::<Splitted>(self.base.control).unwrap() };
+        match o {
+            layout::Orientation::Horizontal => self.panes[index].child.on_added_to_container(self2, start, 0, size, ph),
+            layout::Orientation::Vertical => self.panes[index].child.on_added_to_container(self2, 0, start, pw, size),
+        }
+    }
 }
 
 impl<O: controls::Splitted> NewSplittedInner<O> for CocoaSplitted {
     fn with_uninit_params(ptr: &mut mem::MaybeUninit<O>, first: Box<dyn controls::Control>, second: Box<dyn controls::Control>, orientation: layout::Orientation) -> Self {
         let sp = CocoaSplitted {
             base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner::<O>),
-            splitter: defaults::SPLITTED_POSITION,
-            first: first,
-            second: second,
+            positions: vec![defaults::SPLITTED_POSITION],
+            panes: vec![Pane::new(first), Pane::new(second)],
+            on_splitter_moved: None,
         };
         unsafe {
             let selfptr = ptr as *mut _ as *mut Splitted;
@@ -98,10 +276,9 @@ impl<O: controls::Splitted> NewSplittedInner<O> for CocoaSplitted {
             let delegate: *mut Object = msg_send!(DELEGATE.0, new);
             (&mut *delegate).set_ivar(common::IVAR, selfptr as *mut c_void);
             let () = msg_send![sp.base.control, setDelegate: delegate];
-            let first = sp.first.native_id() as cocoa_id;
-            let second = sp.second.native_id() as cocoa_id;
-            let () = msg_send![sp.base.control, addSubview: first];
-            let () = msg_send![sp.base.control, addSubview: second];
+            for pane in sp.panes.iter() {
+                let () = msg_send![sp.base.control, addSubview: pane.child.native_id() as cocoa_id];
+            }
             let () = msg_send![sp.base.control, setVertical: orientation_to_vertical(orientation)];
             let () = msg_send![sp.base.control, adjustSubviews];
         }
@@ -130,154 +307,140 @@ impl SplittedInner for CocoaSplitted {
     fn set_splitter(&mut self, base: &mut MemberBase, pos: f32) {
         let (m, c, _) = unsafe { Splitted::control_base_parts_mut(base) };
         let pos = pos % 1.0;
-        self.splitter = pos;
+        self.positions[0] = self.positions.get(1).map(|next| pos.min(*next)).unwrap_or(pos);
+        self.constrain_positions(self.length(m, self.base.size(c)));
         self.update_splitter(m, c);
         self.base.invalidate();
     }
     fn splitter(&self) -> f32 {
-        self.splitter
+        self.positions[0]
     }
     fn first(&self) -> &dyn controls::Control {
-        self.first.as_ref()
+        self.panes[0].child.as_ref()
     }
     fn second(&self) -> &dyn controls::Control {
-        self.second.as_ref()
+        self.panes[1].child.as_ref()
     }
     fn first_mut(&mut self) -> &mut dyn controls::Control {
-        self.first.as_mut()
+        self.panes[0].child.as_mut()
     }
     fn second_mut(&mut self) -> &mut dyn controls::Control {
-        self.second.as_mut()
+        self.panes[1].child.as_mut()
     }
 }
 impl MultiContainerInner for CocoaSplitted {
     fn len(&self) -> usize {
-        2
+        self.panes.len()
     }
+    /// Replaces the pane content at `index`, or adds a new last pane if `index` is out of bounds.
     fn set_child_to(&mut self, base: &mut MemberBase, index: usize, mut child: Box<dyn controls::Control>) -> Option<Box<dyn controls::Control>> {
-        match index {
-            0 => unsafe {
-                let self2 = utils::base_to_impl_mut::<Splitted>(base);
-                let sizes = self.first.size();
-                let () = msg_send![self.first.native_id() as cocoa_id, removeFromSuperview];
-                if self.base.root().is_some() {
-                    self.first.on_removed_from_container(self2);
-                }
-                let () = msg_send![child.native_id() as cocoa_id, addSubview: child.native_id() as cocoa_id];
-                if self.base.root().is_some() {
-                    child.on_added_to_container(self2, 0, 0, sizes.0, sizes.1);
-                }
-                mem::swap(&mut self.first, &mut child);
-            },
-            1 => unsafe {
-                let self2 = utils::base_to_impl_mut::<Splitted>(base);
-                let sizes = self.second.size();
-                let () = msg_send![self.second.native_id() as cocoa_id, removeFromSuperview];
-                if self.base.root().is_some() {
-                    self.second.on_removed_from_container(self2);
-                }
-                let () = msg_send![child.native_id() as cocoa_id, addSubview: child.native_id() as cocoa_id];
-                if self.base.root().is_some() {
-                    child.on_added_to_container(self2, 0, 0, sizes.0, sizes.1);
-                }
-                mem::swap(&mut self.second, &mut child);
-            },
-            _ => return None,
+        let self2 = unsafe { utils::base_to_impl_mut::<Splitted>(base) };
+        let old = if index < self.panes.len() {
+            unsafe {
+                let old_id = self.panes[index].child.native_id() as cocoa_id;
+                let () = msg_send![self.base.control, addSubview:child.native_id() as cocoa_id positioned:NS_WINDOW_BELOW relativeTo:old_id];
+                let () = msg_send![old_id, removeFromSuperview];
+            }
+            if self.base.root().is_some() {
+                self.panes[index].child.on_removed_from_container(self2);
+            }
+            mem::swap(&mut self.panes[index].child, &mut child);
+            Some(child)
+        } else {
+            unsafe {
+                let () = msg_send![self.base.control, addSubview: child.native_id() as cocoa_id];
+            }
+            // the new pane takes the second half of the last one
+            let last_start = self.positions.last().cloned().unwrap_or(0.0);
+            self.positions.push((last_start + 1.0) / 2.0);
+            self.panes.push(Pane::new(child));
+            None
+        };
+        let index = cmp::min(index, self.panes.len() - 1);
+        if self.base.root().is_some() {
+            self.add_pane_to_container(index);
         }
         self.base.invalidate();
-        Some(child)
+        old
     }
-    fn remove_child_from(&mut self, _: &mut MemberBase, _: usize) -> Option<Box<dyn controls::Control>> {
-        None
+    /// Panes are removed only while more than two are left.
+    fn remove_child_from(&mut self, _: &mut MemberBase, index: usize) -> Option<Box<dyn controls::Control>> {
+        if index >= self.panes.len() || self.panes.len() <= 2 {
+            return None;
+        }
+        let mut pane = self.panes.remove(index);
+        self.positions.remove(cmp::min(index, self.positions.len() - 1));
+        unsafe {
+            let () = msg_send![pane.child.native_id() as cocoa_id, removeFromSuperview];
+        }
+        if self.base.root().is_some() {
+            pane.child.on_removed_from_container(unsafe { common::member_from_cocoa_id::<Splitted>(self.base.control).unwrap() });
+        }
+        self.base.invalidate();
+        Some(pane.child)
     }
     fn child_at(&self, index: usize) -> Option<&dyn controls::Control> {
-        match index {
-            0 => Some(self.first()),
-            1 => Some(self.second()),
-            _ => None,
-        }
+        self.panes.get(index).map(|pane| pane.child.as_ref())
     }
     fn child_at_mut(&mut self, index: usize) -> Option<&mut dyn controls::Control> {
-        match index {
-            0 => Some(self.first_mut()),
-            1 => Some(self.second_mut()),
-            _ => None,
+        if let Some(pane) = self.panes.get_mut(index) {
+            Some(pane.child.as_mut())
+        } else {
+            None
         }
     }
 }
 
 impl ContainerInner for CocoaSplitted {
     fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
-        match arg {
-            types::FindBy::Id(id) => {
-                if self.first().as_member().id() == id {
-                    return Some(self.first_mut());
-                }
-                if self.second().as_member().id() == id {
-                    return Some(self.second_mut());
-                }
-            }
-            types::FindBy::Tag(tag) => {
-                if let Some(mytag) = self.first.as_member().tag() {
-                    if tag == mytag {
-                        return Some(self.first_mut());
+        for pane in self.panes.as_mut_slice() {
+            let child = &mut pane.child;
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member_mut().id() == *id {
+                        return Some(child.as_mut());
                     }
                 }
-                if let Some(mytag) = self.second.as_member().tag() {
-                    if tag == mytag {
-                        return Some(self.second_mut());
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
                     }
                 }
             }
-        }
-
-        let self2: &mut CocoaSplitted = unsafe { mem::transmute(self as *mut CocoaSplitted) }; // bck is stupid
-        if let Some(c) = self.first_mut().is_container_mut() {
-            let ret = c.find_control_mut(arg.clone());
-            if ret.is_some() {
-                return ret;
-            }
-        }
-        if let Some(c) = self2.second_mut().is_container_mut() {
-            let ret = c.find_control_mut(arg);
-            if ret.is_some() {
+            if let Some(c) = child.is_container_mut() {
+                let ret = c.find_control_mut(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
                 return ret;
             }
         }
         None
     }
     fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
-        match arg {
-            types::FindBy::Id(id) => {
-                if self.first().as_member().id() == id {
-                    return Some(self.first());
-                }
-                if self.second().as_member().id() == id {
-                    return Some(self.second());
-                }
-            }
-            types::FindBy::Tag(tag) => {
-                if let Some(mytag) = self.first.as_member().tag() {
-                    if tag == mytag {
-                        return Some(self.first.as_ref());
+        for pane in self.panes.as_slice() {
+            let child = &pane.child;
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member().id() == *id {
+                        return Some(child.as_ref());
                     }
                 }
-                if let Some(mytag) = self.second.as_member().tag() {
-                    if tag == mytag {
-                        return Some(self.second.as_ref());
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
                     }
                 }
             }
-        }
-        if let Some(c) = self.first().is_container() {
-            let ret = c.find_control(arg.clone());
-            if ret.is_some() {
-                return ret;
-            }
-        }
-        if let Some(c) = self.second().is_container() {
-            let ret = c.find_control(arg);
-            if ret.is_some() {
+            if let Some(c) = child.is_container() {
+                let ret = c.find_control(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
                 return ret;
             }
         }
@@ -302,19 +465,14 @@ impl HasOrientationInner for CocoaSplitted {
 impl ControlInner for CocoaSplitted {
     fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _: i32, _: i32, pw: u16, ph: u16) {
         self.measure(member, control, pw, ph);
-        let (first_size, second_size) = self.children_sizes(member, control);
-        match self.orientation(member) {
-            layout::Orientation::Horizontal => {
-                let h = utils::coord_to_size(ph as i32);
-                let self2: &mut Splitted = unsafe { utils::base_to_impl_mut(member) };
-                self.first.on_added_to_container(self2, 0, 0, first_size, h);
-                self.second.on_added_to_container(self2, first_size as i32, 0, second_size, h);
-            }
-            layout::Orientation::Vertical => {
-                let w = utils::coord_to_size(pw as i32);
-                let self2: &mut Splitted = unsafe { utils::base_to_impl_mut(member) };
-                self.first.on_added_to_container(self2, 0, 0, w, first_size);
-                self.second.on_added_to_container(self2, 0, first_size as i32, w, second_size);
+        self.constrain_positions(self.length(member, self.base.size(control)));
+        let spans = self.pane_spans(self.length(member, self.base.size(control)));
+        let orientation = self.orientation(member);
+        for (pane, (start, size)) in self.panes.iter_mut().zip(spans) {
+            let self2: &mut Splitted = unsafe { utils::base_to_impl_mut(member) };
+            match orientation {
+                layout::Orientation::Horizontal => pane.child.on_added_to_container(self2, start, 0, size, utils::coord_to_size(ph as i32)),
+                layout::Orientation::Vertical => pane.child.on_added_to_container(self2, 0, start, utils::coord_to_size(pw as i32), size),
             }
         }
         self.update_children_layout(member, control);
@@ -322,8 +480,9 @@ impl ControlInner for CocoaSplitted {
     }
     fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
         let self2: &Splitted = unsafe { common::member_from_cocoa_id(self.base.control).unwrap() };
-        self.first.on_removed_from_container(self2);
-        self.second.on_removed_from_container(self2);
+        for pane in self.panes.iter_mut() {
+            pane.child.on_removed_from_container(self2);
+        }
         unsafe {
             self.base.on_removed_from_container();
         }
@@ -412,6 +571,7 @@ impl Drawable for CocoaSplitted {
         self.base.invalidate();
     }
 }
+
 fn orientation_to_vertical(orientation: layout::Orientation) -> BOOL {
     match orientation {
         layout::Orientation::Horizontal => YES,
@@ -437,10 +597,61 @@ unsafe fn register_delegate() -> common::RefClass {
     decl.add_method(sel!(splitViewDidResizeSubviews:), splitter_moved as extern "C" fn(&mut Object, Sel, cocoa_id));
     decl.add_method(sel!(splitView:resizeSubviewsWithOldSize:), splitter_resize_subviews as extern "C" fn(&mut Object, Sel, NSSize, cocoa_id));
     decl.add_method(sel!(shouldAdjustSizeOfSubview:), adjust_subview_size as extern "C" fn(&mut Object, Sel, cocoa_id) -> BOOL);
+    decl.add_method(sel!(splitView:canCollapseSubview:), splitter_can_collapse as extern "C" fn(&mut Object, Sel, cocoa_id, cocoa_id) -> BOOL);
+    decl.add_method(
+        sel!(splitView:shouldCollapseSubview:forDoubleClickOnDividerAtIndex:),
+        splitter_should_collapse as extern "C" fn(&mut Object, Sel, cocoa_id, cocoa_id, NSInteger) -> BOOL,
+    );
+    decl.add_method(
+        sel!(splitView:constrainMinCoordinate:ofSubviewAt:),
+        splitter_constrain_min as extern "C" fn(&mut Object, Sel, cocoa_id, f64, NSInteger) -> f64,
+    );
+    decl.add_method(
+        sel!(splitView:constrainMaxCoordinate:ofSubviewAt:),
+        splitter_constrain_max as extern "C" fn(&mut Object, Sel, cocoa_id, f64, NSInteger) -> f64,
+    );
     decl.add_ivar::<*mut c_void>(common::IVAR);
 
     common::RefClass(decl.register())
 }
+fn divider_range(sp: &mut Splitted, divider: usize) -> (f64, f64) {
+    let (m, c, inner) = sp.as_control_parts_mut();
+    let inner = inner.inner_mut().inner_mut().inner_mut();
+    inner.divider_range(inner.length(m, inner.base.size(c)), divider)
+}
+extern "C" fn splitter_constrain_min(this: &mut Object, _: Sel, _: cocoa_id, proposed: f64, divider: NSInteger) -> f64 {
+    let sp = unsafe { common::member_from_cocoa_id_mut::<Splitted>(this).unwrap() };
+    proposed.max(divider_range(sp, divider as usize).0)
+}
+extern "C" fn splitter_constrain_max(this: &mut Object, _: Sel, _: cocoa_id, proposed: f64, divider: NSInteger) -> f64 {
+    let sp = unsafe { common::member_from_cocoa_id_mut::<Splitted>(this).unwrap() };
+    proposed.min(divider_range(sp, divider as usize).1)
+}
+fn pane_index(sp: &Splitted, subview: cocoa_id) -> Option<usize> {
+    sp.inner().inner().inner().inner().inner().panes.iter().position(|pane| pane.child.native_id() as cocoa_id == subview)
+}
+extern "C" fn splitter_can_collapse(this: &mut Object, _: Sel, _: cocoa_id, subview: cocoa_id) -> BOOL {
+    let sp = unsafe { common::member_from_cocoa_id_mut::<Splitted>(this).unwrap() };
+    match pane_index(sp, subview) {
+        Some(index) if sp.inner().inner().inner().inner().inner().panes[index].options.collapsible => YES,
+        _ => NO,
+    }
+}
+/// Sent for both panes next to the divider, only the one picked by `collapse_target` is toggled. The collapsed state is ours,
+/// so `NSSplitView` is told not to hide the pane itself.
+extern "C" fn splitter_should_collapse(this: &mut Object, _: Sel, _: cocoa_id, subview: cocoa_id, divider: NSInteger) -> BOOL {
+    let sp = unsafe { common::member_from_cocoa_id_mut::<Splitted>(this).unwrap() };
+    let index = pane_index(sp, subview);
+    let (m, c, inner) = sp.as_control_parts_mut();
+    let inner = inner.inner_mut().inner_mut().inner_mut();
+    if index.is_some() && inner.collapse_target(divider as usize) == index {
+        let pane = &mut inner.panes[index.unwrap()];
+        pane.collapsed = !pane.collapsed;
+        inner.update_splitter(m, c);
+        inner.base.invalidate();
+    }
+    NO
+}
 extern "C" fn adjust_subview_size(_: &mut Object, _: Sel, _: cocoa_id) -> BOOL {
     NO
 }
@@ -454,42 +665,68 @@ extern "C" fn splitter_resize_subviews(this: &mut Object, _: Sel, _: NSSize, _:
 extern "C" fn splitter_moved(this: &mut Object, _: Sel, _: cocoa_id) {
     unsafe {
         let sp = common::member_from_cocoa_id_mut::<Splitted>(this).unwrap();
-        let subviews: cocoa_id = msg_send![sp.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().base.control, subviews];
-        let first: cocoa_id = msg_send![subviews, objectAtIndex:0];
-        let first: NSRect = msg_send![first, frame];
-        let second: cocoa_id = msg_send![subviews, objectAtIndex:1];
-        let second: NSRect = msg_send![second, frame];
-        let size: NSRect = msg_send![sp.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().base.control, frame];
-        let o = controls::HasOrientation::orientation(sp);
-        let mut splitter_first = match o {
-            layout::Orientation::Horizontal => (first.size.width / size.size.width),
-            layout::Orientation::Vertical => (first.size.height / size.size.height),
-        } as f32;
-        let splitter_second = match o {
-            layout::Orientation::Horizontal => (second.size.width / size.size.width),
-            layout::Orientation::Vertical => (second.size.height / size.size.height),
-        } as f32;
-        if splitter_first.is_nan() || splitter_second.is_nan() {
+        let sp2 = common::member_from_cocoa_id_mut::<Splitted>(this).unwrap();
+        let inner = sp.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut();
+        let size: NSRect = msg_send![inner.base.control, frame];
+        let o = controls::HasOrientation::orientation(sp2);
+        let length = match o {
+            layout::Orientation::Horizontal => size.size.width,
+            layout::Orientation::Vertical => size.size.height,
+        };
+        if length < 1.0 {
+            return;
+        }
+        let gap = inner.thickness() as f64 / 2.0 + PADDING as f64;
+        // dragging past the minimum of a collapsible pane makes NSSplitView hide it, take it over as our collapsed state
+        let mut collapsed = false;
+        for pane in inner.panes.iter_mut() {
+            let id = pane.child.native_id() as cocoa_id;
+            let hidden: BOOL = msg_send![inner.base.control, isSubviewCollapsed: id];
+            if hidden == YES {
+                let () = msg_send![id, setHidden: NO];
+                pane.collapsed = true;
+                collapsed = true;
+            }
+        }
+        let subviews: cocoa_id = msg_send![inner.base.control, subviews];
+        let mut moved = Vec::new();
+        for divider in 0..inner.positions.len() {
+            if inner.panes[divider].collapsed || inner.panes[divider + 1].collapsed {
+                continue;
+            }
+            let next: cocoa_id = msg_send![subviews, objectAtIndex: divider + 1];
+            let next: NSRect = msg_send![next, frame];
+            let (origin, next_length) = match o {
+                layout::Orientation::Horizontal => (next.origin.x, next.size.width),
+                layout::Orientation::Vertical => (next.origin.y, next.size.height),
+            };
+            // On first appearance NSSplitView loads its own default divider positions, ignoring the sizing of the panes.
+            // We can use it to distinguish the initial appearance from all the following, which have to control our positions.
+            if next_length < 1.0 {
+                continue;
+            }
+            let position = ((origin - gap) / length) as f32;
+            if (inner.positions[divider] - position).abs() > f32::EPSILON {
+                inner.positions[divider] = position;
+                moved.push((divider, position));
+            }
+        }
+        if moved.is_empty() && !collapsed {
             return;
         }
-        let bias = (1.0 - (splitter_first + splitter_second)) / 2.0;
-        splitter_first += bias;
-        let old_splitter = sp.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().splitter;
-        if (old_splitter - splitter_first) != 0.0 {
-            sp.set_skip_draw(true);
-            {
-                let base = common::member_base_from_cocoa_id_mut(this).unwrap();
-                let (m, c, _) = Splitted::control_base_parts_mut(base);
-                let sp = sp.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut();
-                // On first appearance NSSplitView loads its own default divider position, ignoring the sizing of the second child.
-                // We can use it to distinguish the initial appearance from all the following, which have to control our splitter position.
-                if second.size.width >= 1.0 && second.size.height >= 1.0 {
-                    sp.splitter = splitter_first;
-                } 
-                sp.update_children_layout(m, c);
-                sp.draw_children(m, c);
+        sp2.set_skip_draw(true);
+        {
+            let base = common::member_base_from_cocoa_id_mut(this).unwrap();
+            let (m, c, _) = Splitted::control_base_parts_mut(base);
+            inner.update_children_layout(m, c);
+            inner.draw_children(m, c);
+        }
+        sp2.set_skip_draw(false);
+        if let Some(ref mut callback) = inner.on_splitter_moved {
+            let sp3 = common::member_from_cocoa_id_mut::<Splitted>(this).unwrap();
+            for (divider, position) in moved {
+                (callback.as_mut())(sp3, divider, position);
             }
-            sp.set_skip_draw(false);
         }
     }
 }
@@ -503,6 +740,9 @@ extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
 extern "C" fn set_frame_size_inner<O: controls::Splitted>(this: &mut Splitted, _: Sel, param: NSSize) {
     unsafe {
         let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        let (m, _, sp) = this.as_control_parts_mut();
+        let sp = sp.inner_mut().inner_mut().inner_mut();
+        sp.constrain_positions(sp.length(m, (param.width as u16, param.height as u16)));
         this.call_on_size::<O>(param.width as u16, param.height as u16)
     }
 }
@@ -0,0 +1,177 @@
+use crate::common::*;
+
+const NS_FONT_ATTRIBUTE_NAME: &str = "NSFont";
+const NS_FOREGROUND_COLOR_ATTRIBUTE_NAME: &str = "NSColor";
+const NS_PARAGRAPH_STYLE_ATTRIBUTE_NAME: &str = "NSParagraphStyle";
+
+/// sRGB color with alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 0xff }
+    }
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+    pub(crate) unsafe fn to_native(&self) -> cocoa_id {
+        msg_send![class!(NSColor), colorWithSRGBRed:self.r as f64 / 255.0 green:self.g as f64 / 255.0 blue:self.b as f64 / 255.0 alpha:self.a as f64 / 255.0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontWeight {
+    Light,
+    Regular,
+    Medium,
+    Semibold,
+    Bold,
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::Regular
+    }
+}
+
+impl FontWeight {
+    /// `NSFontWeight` of the system font.
+    fn system_weight(&self) -> f64 {
+        match *self {
+            FontWeight::Light => -0.4,
+            FontWeight::Regular => 0.0,
+            FontWeight::Medium => 0.23,
+            FontWeight::Semibold => 0.3,
+            FontWeight::Bold => 0.4,
+        }
+    }
+    /// `NSFontManager` weight on its 0 to 15 scale.
+    fn manager_weight(&self) -> NSInteger {
+        match *self {
+            FontWeight::Light => 3,
+            FontWeight::Regular => 5,
+            FontWeight::Medium => 6,
+            FontWeight::Semibold => 8,
+            FontWeight::Bold => 9,
+        }
+    }
+}
+
+/// Font description, unset parts fall back to the system font.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Font {
+    pub family: Option<String>,
+    /// Size in points.
+    pub size: Option<f32>,
+    pub weight: FontWeight,
+}
+
+impl Font {
+    pub(crate) unsafe fn to_native(&self) -> cocoa_id {
+        let size = match self.size {
+            Some(size) => size as f64,
+            None => msg_send![class!(NSFont), systemFontSize],
+        };
+        if let Some(ref family) = self.family {
+            let family = NSString::alloc(nil).init_str(family);
+            let manager: cocoa_id = msg_send![class!(NSFontManager), sharedFontManager];
+            let font: cocoa_id = msg_send![manager, fontWithFamily:family traits:0 as NSUInteger weight:self.weight.manager_weight() size:size];
+            let () = msg_send![family, release];
+            if !font.is_null() {
+                return font;
+            }
+        }
+        msg_send![class!(NSFont), systemFontOfSize:size weight:self.weight.system_weight()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlignment {
+    /// Left for left-to-right languages, right otherwise.
+    Natural,
+    Left,
+    Center,
+    Right,
+    Justified,
+}
+
+impl Default for TextAlignment {
+    fn default() -> Self {
+        TextAlignment::Natural
+    }
+}
+
+impl TextAlignment {
+    pub(crate) fn to_native(&self) -> NSInteger {
+        match *self {
+            TextAlignment::Left => 0,
+            TextAlignment::Right => 1,
+            TextAlignment::Center => 2,
+            TextAlignment::Justified => 3,
+            TextAlignment::Natural => 4,
+        }
+    }
+}
+
+/// Look of the text of a control, `None` colors keep the system ones.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextStyle {
+    pub text_color: Option<Color>,
+    pub background_color: Option<Color>,
+    pub font: Font,
+    pub alignment: TextAlignment,
+}
+
+/// Text styling of `Text`, `Button` and `Frame`. Changing the font re-measures `WrapContent` controls.
+pub trait HasTextStyle {
+    fn text_style(&self) -> &TextStyle;
+    fn set_text_style(&mut self, style: TextStyle);
+
+    fn set_text_color(&mut self, color: Option<Color>) {
+        let mut style = self.text_style().clone();
+        style.text_color = color;
+        self.set_text_style(style);
+    }
+    fn set_background_color(&mut self, color: Option<Color>) {
+        let mut style = self.text_style().clone();
+        style.background_color = color;
+        self.set_text_style(style);
+    }
+    fn set_font(&mut self, font: Font) {
+        let mut style = self.text_style().clone();
+        style.font = font;
+        self.set_text_style(style);
+    }
+    fn set_text_alignment(&mut self, alignment: TextAlignment) {
+        let mut style = self.text_style().clone();
+        style.alignment = alignment;
+        self.set_text_style(style);
+    }
+}
+
+/// Attributes dictionary for `NSAttributedString`, `nil` values are skipped.
+pub(crate) unsafe fn attributes(font: cocoa_id, color: cocoa_id, alignment: Option<TextAlignment>) -> cocoa_id {
+    let attributes: cocoa_id = msg_send![class!(NSMutableDictionary), dictionary];
+    let set = |key: &str, value: cocoa_id| {
+        if !value.is_null() {
+            let key = NSString::alloc(nil).init_str(key);
+            let () = msg_send![attributes, setObject:value forKey:key];
+            let () = msg_send![key, release];
+        }
+    };
+    set(NS_FONT_ATTRIBUTE_NAME, font);
+    set(NS_FOREGROUND_COLOR_ATTRIBUTE_NAME, color);
+    if let Some(alignment) = alignment {
+        let paragraph: cocoa_id = msg_send![class!(NSMutableParagraphStyle), new];
+        let () = msg_send![paragraph, setAlignment: alignment.to_native()];
+        set(NS_PARAGRAPH_STYLE_ATTRIBUTE_NAME, paragraph);
+        let () = msg_send![paragraph, release];
+    }
+    attributes
+}
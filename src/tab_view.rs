@@ -0,0 +1,480 @@
+use crate::common::{self, *};
+
+lazy_static! {
+    static ref WINDOW_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiTabView", BASE_CLASS, |decl| {
+            decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(tabView:didSelectTabViewItem:), tab_selected as extern "C" fn(&mut Object, Sel, cocoa_id, cocoa_id));
+        })
+    };
+    static ref PAGE_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiTabPage", "NSView", |decl| {
+            decl.add_method(sel!(isFlipped), is_flipped as extern "C" fn(&mut Object, Sel) -> BOOL);
+        })
+    };
+    static ref ITEM_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiTabViewItem", "NSTabViewItem", |decl| {
+            decl.add_method(sel!(sizeOfLabel:), size_of_label as extern "C" fn(&mut Object, Sel, BOOL) -> NSSize);
+            decl.add_method(sel!(drawLabel:inRect:), draw_label as extern "C" fn(&mut Object, Sel, BOOL, NSRect));
+        })
+    };
+}
+
+const BASE_CLASS: &str = "NSTabView";
+const ICON_SPACING: f64 = 4.0;
+const NS_COMPOSITING_OPERATION_SOURCE_OVER: NSUInteger = 2;
+
+pub type TabView = AMember<AControl<AContainer<AMultiContainer<CocoaTabView>>>>;
+
+/// Receives the index of the newly selected tab.
+pub type OnTabChanged = Box<dyn FnMut(&mut dyn controls::Member, usize)>;
+
+struct Tab {
+    child: Box<dyn controls::Control>,
+    item: cocoa_id,
+}
+
+#[repr(C)]
+pub struct CocoaTabView {
+    base: common::CocoaControlBase<TabView>,
+    tabs: Vec<Tab>,
+    on_tab_changed: Option<OnTabChanged>,
+    /// Space taken by the tab strip and the bezel, the difference between the frame and the `contentRect`.
+    content_inset: (i32, i32),
+}
+
+/// Tab-specific part of `TabView`, children are managed through `controls::MultiContainer`.
+/// New tabs are labeled with the child tag, or their number if it has none.
+pub trait TabViewExt {
+    fn set_tab_label(&mut self, index: usize, label: &str);
+    fn tab_label(&self, index: usize) -> Option<String>;
+    /// Icon drawn before the tab label, scaled to the label height.
+    fn set_tab_icon(&mut self, index: usize, icon: Option<&image::DynamicImage>);
+    fn selected_tab(&self) -> Option<usize>;
+    fn select_tab(&mut self, index: usize);
+    fn on_tab_changed(&mut self, callback: Option<OnTabChanged>);
+}
+
+impl TabViewExt for TabView {
+    fn set_tab_label(&mut self, index: usize, label: &str) {
+        if let Some(tab) = self.inner().inner().inner().inner().tabs.get(index) {
+            unsafe {
+                let label = NSString::alloc(nil).init_str(label);
+                let () = msg_send![tab.item, setLabel: label];
+                let () = msg_send![label, release];
+            }
+        }
+    }
+    fn tab_label(&self, index: usize) -> Option<String> {
+        self.inner().inner().inner().inner().tabs.get(index).map(|tab| unsafe { common::nsstring_to_string(msg_send![tab.item, label]) })
+    }
+    fn set_tab_icon(&mut self, index: usize, icon: Option<&image::DynamicImage>) {
+        if let Some(tab) = self.inner().inner().inner().inner().tabs.get(index) {
+            unsafe {
+                set_item_icon(tab.item, icon.map(|icon| common::image_to_native(icon)).unwrap_or(nil));
+                // makes the tab view lay out the strip again with the new label size
+                let label: cocoa_id = msg_send![tab.item, label];
+                let () = msg_send![tab.item, setLabel: label];
+            }
+        }
+    }
+    fn selected_tab(&self) -> Option<usize> {
+        self.inner().inner().inner().inner().selected()
+    }
+    fn select_tab(&mut self, index: usize) {
+        let tab_view = self.inner_mut().inner_mut().inner_mut().inner_mut();
+        if let Some(tab) = tab_view.tabs.get(index) {
+            unsafe {
+                let () = msg_send![tab_view.base.control, selectTabViewItem: tab.item];
+            }
+        }
+    }
+    fn on_tab_changed(&mut self, callback: Option<OnTabChanged>) {
+        self.inner_mut().inner_mut().inner_mut().inner_mut().on_tab_changed = callback;
+    }
+}
+
+impl CocoaTabView {
+    fn with_uninit(ptr: &mut mem::MaybeUninit<TabView>) -> Self {
+        let tv = CocoaTabView {
+            base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner),
+            tabs: Vec::new(),
+            on_tab_changed: None,
+            content_inset: unsafe { content_inset() },
+        };
+        let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
+        unsafe {
+            (&mut *tv.base.control).set_ivar(common::IVAR, selfptr);
+            let () = msg_send![tv.base.control, setDelegate: tv.base.control];
+        }
+        tv
+    }
+    pub fn new() -> Box<TabView> {
+        let mut b: Box<mem::MaybeUninit<TabView>> = Box::new_uninit();
+        let ab = AMember::with_inner(
+            AControl::with_inner(
+                AContainer::with_inner(
+                    AMultiContainer::with_inner(
+                        Self::with_uninit(b.as_mut()),
+                    ),
+                )
+            ),
+        );
+        unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        }
+    }
+    fn selected(&self) -> Option<usize> {
+        unsafe {
+            let item: cocoa_id = msg_send![self.base.control, selectedTabViewItem];
+            self.tabs.iter().position(|tab| tab.item == item)
+        }
+    }
+    fn page_size(&self, (w, h): (u16, u16)) -> (u16, u16) {
+        (utils::coord_to_size(w as i32 - self.content_inset.0), utils::coord_to_size(h as i32 - self.content_inset.1))
+    }
+}
+
+impl Drop for CocoaTabView {
+    fn drop(&mut self) {
+        for tab in self.tabs.drain(..) {
+            unsafe {
+                set_item_icon(tab.item, nil);
+                let () = msg_send![tab.item, release];
+            }
+        }
+    }
+}
+
+impl MultiContainerInner for CocoaTabView {
+    fn len(&self) -> usize {
+        self.tabs.len()
+    }
+    fn set_child_to(&mut self, base: &mut MemberBase, index: usize, new: Box<dyn controls::Control>) -> Option<Box<dyn controls::Control>> {
+        let old = self.remove_child_from(base, index);
+        let index = cmp::min(index, self.tabs.len());
+
+        let this = unsafe { common::member_from_cocoa_id_mut::<TabView>(self.base.control).unwrap() };
+        let item = unsafe {
+            let child_id = new.native_id() as cocoa_id;
+            (&mut *child_id).set_ivar(common::IVAR_PARENT, self.base.control as *mut c_void);
+            let page: cocoa_id = msg_send![PAGE_CLASS.0, alloc];
+            let page: cocoa_id = msg_send![page, initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0))];
+            let () = msg_send![page, addSubview: child_id];
+
+            let label = match new.as_member().tag() {
+                Some(tag) => tag.to_string(),
+                None => format!("{}", index + 1),
+            };
+            let label = NSString::alloc(nil).init_str(&label);
+            let item: cocoa_id = msg_send![ITEM_CLASS.0, alloc];
+            let item: cocoa_id = msg_send![item, initWithIdentifier: nil];
+            let () = msg_send![item, setLabel: label];
+            let () = msg_send![item, setView: page];
+            let () = msg_send![label, release];
+            let () = msg_send![page, release];
+            item
+        };
+        // the selection callback may fire while inserting, the tab has to be known by then
+        self.tabs.insert(index, Tab { child: new, item });
+        unsafe {
+            let () = msg_send![self.base.control, insertTabViewItem:item atIndex:index as NSInteger];
+        }
+
+        if self.base.root().is_some() {
+            let (pw, ph) = self.page_size(this.inner().base.measured);
+            self.tabs[index].child.on_added_to_container(this, 0, 0, pw, ph);
+        }
+        self.base.invalidate();
+
+        old
+    }
+    fn remove_child_from(&mut self, _: &mut MemberBase, index: usize) -> Option<Box<dyn controls::Control>> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let mut tab = self.tabs.remove(index);
+        unsafe {
+            let child_id = tab.child.native_id() as cocoa_id;
+            *(&mut *child_id).get_mut_ivar::<*mut c_void>(common::IVAR_PARENT) = ptr::null_mut();
+            let () = msg_send![child_id, removeFromSuperview];
+            let () = msg_send![self.base.control, removeTabViewItem: tab.item];
+            set_item_icon(tab.item, nil);
+            let () = msg_send![tab.item, release];
+        }
+        if self.base.root().is_some() {
+            tab.child.on_removed_from_container(unsafe { common::member_from_cocoa_id::<TabView>(self.base.control).unwrap() });
+        }
+        self.base.invalidate();
+
+        Some(tab.child)
+    }
+    fn child_at(&self, index: usize) -> Option<&dyn controls::Control> {
+        self.tabs.get(index).map(|tab| tab.child.as_ref())
+    }
+    fn child_at_mut(&mut self, index: usize) -> Option<&mut dyn controls::Control> {
+        if let Some(tab) = self.tabs.get_mut(index) {
+            Some(tab.child.as_mut())
+        } else {
+            None
+        }
+    }
+}
+
+impl ContainerInner for CocoaTabView {
+    fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
+        for tab in self.tabs.as_mut_slice() {
+            let child = &mut tab.child;
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member_mut().id() == *id {
+                        return Some(child.as_mut());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container_mut() {
+                let ret = c.find_control_mut(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+    fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
+        for tab in self.tabs.as_slice() {
+            let child = &tab.child;
+            match arg {
+                types::FindBy::Id(ref id) => {
+                    if child.as_member().id() == *id {
+                        return Some(child.as_ref());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container() {
+                let ret = c.find_control(arg.clone());
+                if ret.is_none() {
+                    continue;
+                }
+                return ret;
+            }
+        }
+        None
+    }
+}
+
+impl ControlInner for CocoaTabView {
+    fn on_added_to_container(&mut self, member: &mut MemberBase, control: &mut ControlBase, _parent: &dyn controls::Container, _x: i32, _y: i32, pw: u16, ph: u16) {
+        self.measure(member, control, pw, ph);
+        let (pw, ph) = self.page_size(control.measured);
+        let this = unsafe { common::member_from_cocoa_id_mut::<TabView>(self.base.control).unwrap() };
+        for tab in self.tabs.iter_mut() {
+            tab.child.on_added_to_container(this, 0, 0, pw, ph);
+        }
+    }
+    fn on_removed_from_container(&mut self, _: &mut MemberBase, _: &mut ControlBase, _: &dyn controls::Container) {
+        let this = unsafe { common::member_from_cocoa_id::<TabView>(self.base.control).unwrap() };
+        for tab in self.tabs.iter_mut() {
+            tab.child.on_removed_from_container(this);
+        }
+        unsafe {
+            self.base.on_removed_from_container();
+        }
+    }
+
+    fn parent(&self) -> Option<&dyn controls::Member> {
+        self.base.parent()
+    }
+    fn parent_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.parent_mut()
+    }
+    fn root(&self) -> Option<&dyn controls::Member> {
+        self.base.root()
+    }
+    fn root_mut(&mut self) -> Option<&mut dyn controls::Member> {
+        self.base.root_mut()
+    }
+
+    #[cfg(feature = "markup")]
+    fn fill_from_markup(&mut self, base: &mut MemberBase, _control: &mut ControlBase, markup: &plygui_api::markup::Markup, registry: &mut plygui_api::markup::MarkupRegistry) {
+        fill_from_markup_base!(self, base, markup, registry, TabView, ["TabView"]);
+        fill_from_markup_children!(self, base, markup, registry);
+    }
+}
+
+impl HasNativeIdInner for CocoaTabView {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.base.control.into()
+    }
+}
+
+impl HasSizeInner for CocoaTabView {
+    fn on_size_set(&mut self, _: &mut MemberBase, _: (u16, u16)) -> bool {
+        self.base.invalidate();
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaTabView {
+    fn on_visibility_set(&mut self, _base: &mut MemberBase, value: types::Visibility) -> bool {
+        self.base.on_set_visibility(value)
+    }
+}
+
+impl MemberInner for CocoaTabView {}
+
+impl HasLayoutInner for CocoaTabView {
+    fn on_layout_changed(&mut self, _: &mut MemberBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Drawable for CocoaTabView {
+    fn draw(&mut self, _member: &mut MemberBase, control: &mut ControlBase) {
+        self.base.draw(control.coords, control.measured);
+        for tab in self.tabs.iter_mut() {
+            tab.child.draw(Some((0, 0)));
+        }
+    }
+    fn measure(&mut self, _member: &mut MemberBase, control: &mut ControlBase, parent_width: u16, parent_height: u16) -> (u16, u16, bool) {
+        let old_size = control.measured;
+        control.measured = match control.visibility {
+            types::Visibility::Gone => (0, 0),
+            _ => {
+                let available = (
+                    match control.layout.width {
+                        layout::Size::Exact(w) => w,
+                        _ => parent_width,
+                    },
+                    match control.layout.height {
+                        layout::Size::Exact(h) => h,
+                        _ => parent_height,
+                    },
+                );
+                let (pw, ph) = self.page_size(available);
+                let mut w = 0;
+                let mut h = 0;
+                for tab in self.tabs.iter_mut() {
+                    let (cw, ch, _) = tab.child.measure(pw, ph);
+                    w = cmp::max(w, cw);
+                    h = cmp::max(h, ch);
+                }
+                let w = match control.layout.width {
+                    layout::Size::Exact(w) => w,
+                    layout::Size::MatchParent => parent_width,
+                    layout::Size::WrapContent => utils::coord_to_size(w as i32 + self.content_inset.0),
+                };
+                let h = match control.layout.height {
+                    layout::Size::Exact(h) => h,
+                    layout::Size::MatchParent => parent_height,
+                    layout::Size::WrapContent => utils::coord_to_size(h as i32 + self.content_inset.1),
+                };
+                (w, h)
+            }
+        };
+        (control.measured.0, control.measured.1, control.measured != old_size)
+    }
+    fn invalidate(&mut self, _: &mut MemberBase, _: &mut ControlBase) {
+        self.base.invalidate();
+    }
+}
+
+impl Spawnable for CocoaTabView {
+    fn spawn() -> Box<dyn controls::Control> {
+        Self::new()
+    }
+}
+
+extern "C" fn tab_selected(this: &mut Object, _: Sel, _: cocoa_id, _: cocoa_id) {
+    let tab_view = unsafe { common::member_from_cocoa_id_mut::<TabView>(this) }.unwrap();
+    let tab_view2 = unsafe { common::member_from_cocoa_id_mut::<TabView>(this) }.unwrap();
+    let inner = tab_view.inner_mut().inner_mut().inner_mut().inner_mut();
+    if let Some(index) = inner.selected() {
+        if let Some(ref mut callback) = inner.on_tab_changed {
+            (callback.as_mut())(tab_view2, index);
+        }
+    }
+}
+/// Measures the tab strip and bezel of a default tab view through its `contentRect`.
+unsafe fn content_inset() -> (i32, i32) {
+    let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1000.0, 1000.0));
+    let tab_view: cocoa_id = msg_send![class!(NSTabView), alloc];
+    let tab_view: cocoa_id = msg_send![tab_view, initWithFrame: frame];
+    let content: NSRect = msg_send![tab_view, contentRect];
+    let () = msg_send![tab_view, release];
+    ((frame.size.width - content.size.width).ceil() as i32, (frame.size.height - content.size.height).ceil() as i32)
+}
+/// Replaces the icon drawn before the label of the tab item, takes ownership of `icon`.
+unsafe fn set_item_icon(item: cocoa_id, icon: cocoa_id) {
+    let old = (&mut *item).get_mut_ivar::<*mut c_void>(common::IVAR);
+    if !old.is_null() {
+        let () = msg_send![*old as cocoa_id, release];
+    }
+    *old = icon as *mut c_void;
+}
+/// Width the icon takes when drawn `height` points tall, including the spacing to the label.
+unsafe fn item_icon_width(icon: cocoa_id, height: f64) -> f64 {
+    let size: NSSize = msg_send![icon, size];
+    if size.height > 0.0 {
+        size.width * height / size.height + ICON_SPACING
+    } else {
+        0.0
+    }
+}
+extern "C" fn size_of_label(this: &mut Object, _: Sel, truncate: BOOL) -> NSSize {
+    unsafe {
+        let mut size: NSSize = msg_send![super(this, Class::get("NSTabViewItem").unwrap()), sizeOfLabel: truncate];
+        let icon = *this.get_ivar::<*mut c_void>(common::IVAR) as cocoa_id;
+        if !icon.is_null() {
+            size.width += item_icon_width(icon, size.height);
+        }
+        size
+    }
+}
+extern "C" fn draw_label(this: &mut Object, _: Sel, truncate: BOOL, mut rect: NSRect) {
+    unsafe {
+        let icon = *this.get_ivar::<*mut c_void>(common::IVAR) as cocoa_id;
+        if !icon.is_null() {
+            let width = item_icon_width(icon, rect.size.height);
+            let target = NSRect::new(rect.origin, NSSize::new(width - ICON_SPACING, rect.size.height));
+            let () = msg_send![icon, drawInRect:target fromRect:NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0)) operation:NS_COMPOSITING_OPERATION_SOURCE_OVER fraction:1.0f64 respectFlipped:YES hints:nil];
+            rect.origin.x += width;
+            rect.size.width -= width;
+        }
+        let () = msg_send![super(this, Class::get("NSTabViewItem").unwrap()), drawLabel:truncate inRect:rect];
+    }
+}
+extern "C" fn is_flipped(_: &mut Object, _: Sel) -> BOOL {
+    YES
+}
+extern "C" fn set_frame_size(this: &mut Object, sel: Sel, param: NSSize) {
+    unsafe {
+        let b = common::member_from_cocoa_id_mut::<TabView>(this).unwrap();
+        let b2 = common::member_from_cocoa_id_mut::<TabView>(this).unwrap();
+        (b.inner().inner().inner().inner().base.resize_handler)(b2, sel, param)
+    }
+}
+extern "C" fn set_frame_size_inner(this: &mut TabView, _: Sel, param: NSSize) {
+    unsafe {
+        let () = msg_send![super(this.inner_mut().inner_mut().inner_mut().inner_mut().base.control, Class::get(BASE_CLASS).unwrap()), setFrameSize: param];
+        this.call_on_size::<TabView>(param.width as u16, param.height as u16)
+    }
+}
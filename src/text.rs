@@ -1,4 +1,6 @@
 use crate::common::{self, *};
+use crate::rich_text::{self, RichSpan};
+use crate::style::{HasTextStyle, TextStyle};
 
 use std::os::raw::c_char;
 
@@ -6,6 +8,7 @@ lazy_static! {
     static ref WINDOW_CLASS: common::RefClass = unsafe {
         register_window_class("PlyguiText", BASE_CLASS, |decl| {
             decl.add_method(sel!(setFrameSize:), set_frame_size as extern "C" fn(&mut Object, Sel, NSSize));
+            decl.add_method(sel!(textView:clickedOnLink:atIndex:), text_clicked_on_link as extern "C" fn(&mut Object, Sel, cocoa_id, cocoa_id, NSUInteger) -> BOOL);
         })
     };
 }
@@ -17,12 +20,190 @@ pub type Text = AMember<AControl<AText<CocoaText>>>;
 #[repr(C)]
 pub struct CocoaText {
     base: common::CocoaControlBase<Text>,
+    style: TextStyle,
+    rich: Option<Vec<RichSpan>>,
+    on_link_click: Option<OnLinkClick>,
+    word_wrap: bool,
+    truncation: Truncation,
+    max_lines: u16,
+}
+
+/// Where the ellipsis goes when the text does not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    /// The text is clipped.
+    None,
+    Head,
+    Middle,
+    Tail,
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Truncation::None
+    }
+}
+
+/// Receives the clicked link target, returns `false` to let the system open it.
+pub type OnLinkClick = Box<dyn FnMut(&mut dyn controls::Member, &str) -> bool>;
+
+/// Cocoa-specific `Text` functionality.
+pub trait TextExt {
+    /// Replaces the label with rich text: `<b>`, `<i>`, `<tt>` (monospace), `<color=#rrggbb>` and `<a=url>` spans,
+    /// `&lt;`, `&gt;` and `&amp;` escapes. `HasLabel::set_label` switches back to plain text.
+    fn set_markup(&mut self, markup: &str);
+    fn on_link_click(&mut self, callback: Option<OnLinkClick>);
+    /// Wraps the lines at word boundaries to the width available from the parent. Off by default.
+    fn set_word_wrap(&mut self, wrap: bool);
+    fn is_word_wrap(&self) -> bool;
+    /// Truncates the last visible line with an ellipsis instead of clipping it.
+    fn set_truncation(&mut self, truncation: Truncation);
+    fn truncation(&self) -> Truncation;
+    /// Limits the number of displayed lines, `0` for no limit.
+    fn set_max_lines(&mut self, max_lines: u16);
+    fn max_lines(&self) -> u16;
+}
+
+impl TextExt for Text {
+    fn set_markup(&mut self, markup: &str) {
+        let text = self.inner_mut().inner_mut().inner_mut();
+        text.rich = Some(rich_text::parse(markup));
+        text.apply_rich();
+        text.base.invalidate();
+    }
+    fn on_link_click(&mut self, callback: Option<OnLinkClick>) {
+        self.inner_mut().inner_mut().inner_mut().on_link_click = callback;
+    }
+    fn set_word_wrap(&mut self, wrap: bool) {
+        let text = self.inner_mut().inner_mut().inner_mut();
+        text.word_wrap = wrap;
+        text.apply_line_layout();
+        text.base.invalidate();
+    }
+    fn is_word_wrap(&self) -> bool {
+        self.inner().inner().inner().word_wrap
+    }
+    fn set_truncation(&mut self, truncation: Truncation) {
+        let text = self.inner_mut().inner_mut().inner_mut();
+        text.truncation = truncation;
+        text.apply_line_layout();
+        text.base.invalidate();
+    }
+    fn truncation(&self) -> Truncation {
+        self.inner().inner().inner().truncation
+    }
+    fn set_max_lines(&mut self, max_lines: u16) {
+        let text = self.inner_mut().inner_mut().inner_mut();
+        text.max_lines = max_lines;
+        text.apply_line_layout();
+        text.base.invalidate();
+    }
+    fn max_lines(&self) -> u16 {
+        self.inner().inner().inner().max_lines
+    }
+}
+
+impl CocoaText {
+    fn line_break_mode(&self) -> NSUInteger {
+        match self.truncation {
+            Truncation::None if self.word_wrap => common::NS_LINE_BREAK_BY_WORD_WRAPPING,
+            Truncation::None => common::NS_LINE_BREAK_BY_CLIPPING,
+            Truncation::Head => common::NS_LINE_BREAK_BY_TRUNCATING_HEAD,
+            Truncation::Middle => common::NS_LINE_BREAK_BY_TRUNCATING_MIDDLE,
+            Truncation::Tail => common::NS_LINE_BREAK_BY_TRUNCATING_TAIL,
+        }
+    }
+    /// Whether the text is laid out within the parent width rather than on unbounded lines.
+    fn is_constrained(&self) -> bool {
+        self.word_wrap || self.truncation != Truncation::None || self.max_lines > 0
+    }
+    /// Lines the text is limited to. Truncating without wrapping keeps a single line, as `NSTextContainer` wraps
+    /// the truncated modes otherwise.
+    fn line_limit(&self) -> NSUInteger {
+        if !self.word_wrap && self.truncation != Truncation::None {
+            1
+        } else {
+            self.max_lines as NSUInteger
+        }
+    }
+    fn apply_line_layout(&mut self) {
+        unsafe {
+            let container: cocoa_id = msg_send![self.base.control, textContainer];
+            let () = msg_send![container, setWidthTracksTextView: YES];
+            let () = msg_send![container, setLineBreakMode: self.line_break_mode()];
+            let () = msg_send![container, setMaximumNumberOfLines: self.line_limit()];
+        }
+    }
+    /// Sends the style fields that differ from `old`, untouched ones keep what the view already has.
+    fn apply_style(&mut self, old: &TextStyle) {
+        unsafe {
+            let control = self.base.control;
+            if self.style.font != old.font {
+                let () = msg_send![control, setFont: self.style.font.to_native()];
+            }
+            if self.style.text_color != old.text_color {
+                let () = msg_send![control, setTextColor: self.text_color()];
+            }
+            if self.style.alignment != old.alignment {
+                let () = msg_send![control, setAlignment: self.style.alignment.to_native()];
+            }
+            if self.style.background_color != old.background_color {
+                match self.style.background_color {
+                    Some(color) => {
+                        let () = msg_send![control, setBackgroundColor: color.to_native()];
+                        let () = msg_send![control, setDrawsBackground: YES];
+                    }
+                    None => {
+                        let () = msg_send![control, setDrawsBackground: NO];
+                    }
+                }
+            }
+        }
+        self.apply_rich();
+    }
+    unsafe fn text_color(&self) -> cocoa_id {
+        match self.style.text_color {
+            Some(color) => color.to_native(),
+            None => msg_send![class!(NSColor), textColor],
+        }
+    }
+    fn apply_rich(&mut self) {
+        unsafe {
+            let control = self.base.control;
+            let has_links = self.rich.as_ref().map(|spans| rich_text::has_links(spans)).unwrap_or(false);
+            let () = msg_send![control, setSelectable: if has_links { YES } else { NO }];
+            if let Some(ref spans) = self.rich {
+                let attributed = rich_text::to_native(spans, &self.style);
+                let storage: cocoa_id = msg_send![control, textStorage];
+                let () = msg_send![storage, setAttributedString: attributed];
+                let () = msg_send![attributed, release];
+            }
+        }
+    }
+}
+
+impl HasTextStyle for Text {
+    fn text_style(&self) -> &TextStyle {
+        &self.inner().inner().inner().style
+    }
+    fn set_text_style(&mut self, style: TextStyle) {
+        let text = self.inner_mut().inner_mut().inner_mut();
+        let old = mem::replace(&mut text.style, style);
+        text.apply_style(&old);
+        text.base.invalidate();
+    }
 }
 
 impl<O: controls::Text> NewTextInner<O> for CocoaText {
     fn with_uninit(ptr: &mut mem::MaybeUninit<O>) -> Self {
         let tx = CocoaText {
             base: common::CocoaControlBase::with_params(*WINDOW_CLASS, set_frame_size_inner::<O>),
+            style: TextStyle::default(),
+            rich: None,
+            on_link_click: None,
+            word_wrap: false,
+            truncation: Truncation::None,
+            max_lines: 0,
         };
         let selfptr = ptr as *mut _ as *mut ::std::os::raw::c_void;
         unsafe {
@@ -30,6 +211,7 @@ impl<O: controls::Text> NewTextInner<O> for CocoaText {
             let () = msg_send![tx.base.control, setDrawsBackground: NO];
             let () = msg_send![tx.base.control, setEditable: NO];
             let () = msg_send![tx.base.control, setSelectable: NO];
+            let () = msg_send![tx.base.control, setDelegate: tx.base.control];
         }
         tx
     }
@@ -63,10 +245,20 @@ impl HasLabelInner for CocoaText {
         }
     }
     fn set_label(&mut self, _: &mut MemberBase, label: Cow<str>) {
+        let was_rich = self.rich.take().is_some();
         unsafe {
             let title = NSString::alloc(cocoa::base::nil).init_str(&label);
             let () = msg_send![self.base.control, setString: title];
             let () = msg_send![title, release];
+            if was_rich {
+                // the plain text inherits the attributes of the rich one otherwise
+                let () = msg_send![self.base.control, setFont: self.style.font.to_native()];
+                let () = msg_send![self.base.control, setTextColor: self.text_color()];
+                let () = msg_send![self.base.control, setAlignment: self.style.alignment.to_native()];
+            }
+        }
+        if was_rich {
+            self.apply_rich();
         }
     }
 }
@@ -143,24 +335,30 @@ impl Drawable for CocoaText {
         control.measured = match control.visibility {
             types::Visibility::Gone => (0, 0),
             _ => unsafe {
-                let mut label_size = (0, 0);
+                let available = match control.layout.width {
+                    layout::Size::Exact(w) => w,
+                    _ => parent_width,
+                };
+                let mut label_size = None;
+                let mut measure_label = || {
+                    *label_size.get_or_insert_with(|| {
+                        common::measure_attributed_constrained(
+                            msg_send![self.base.control, textStorage],
+                            if self.is_constrained() { Some(available) } else { None },
+                            self.line_break_mode(),
+                            self.line_limit(),
+                        )
+                    })
+                };
                 let w = match control.layout.width {
                     layout::Size::MatchParent => parent_width as i32,
                     layout::Size::Exact(w) => w as i32,
-                    layout::Size::WrapContent => {
-                        label_size = common::measure_nsstring(msg_send![self.base.control, string]);
-                        label_size.0 as i32
-                    }
+                    layout::Size::WrapContent => measure_label().0 as i32,
                 };
                 let h = match control.layout.height {
                     layout::Size::MatchParent => parent_height as i32,
                     layout::Size::Exact(h) => h as i32,
-                    layout::Size::WrapContent => {
-                        if label_size.1 < 1 {
-                            label_size = common::measure_nsstring(msg_send![self.base.control, string]);
-                        }
-                        label_size.1 as i32
-                    }
+                    layout::Size::WrapContent => measure_label().1 as i32,
                 };
                 (cmp::max(0, w) as u16, cmp::max(0, h) as u16)
             },
@@ -189,3 +387,55 @@ extern "C" fn set_frame_size_inner<O: controls::Text>(this: &mut Text, _: Sel, p
         this.call_on_size::<O>(param.width as u16, param.height as u16)
     }
 }
+extern "C" fn text_clicked_on_link(this: &mut Object, _: Sel, _: cocoa_id, link: cocoa_id, _: NSUInteger) -> BOOL {
+    unsafe {
+        let is_url: BOOL = msg_send![link, isKindOfClass: class!(NSURL)];
+        let link = if is_url == YES { common::nsstring_to_string(msg_send![link, absoluteString]) } else { common::nsstring_to_string(link) };
+        let t = common::member_from_cocoa_id_mut::<Text>(this).unwrap();
+        let t2 = common::member_from_cocoa_id_mut::<Text>(this).unwrap();
+        if let Some(ref mut callback) = t.inner_mut().inner_mut().inner_mut().on_link_click {
+            if (callback.as_mut())(t2, link.as_str()) {
+                return YES;
+            }
+        }
+        NO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn attribute(control: cocoa_id, name: &str) -> cocoa_id {
+        let storage: cocoa_id = msg_send![control, textStorage];
+        let name = NSString::alloc(nil).init_str(name);
+        let value: cocoa_id = msg_send![storage, attribute:name atIndex:0 as NSUInteger effectiveRange:nil];
+        let () = msg_send![name, release];
+        value
+    }
+
+    #[test]
+    fn plain_label_drops_markup_attributes() {
+        let mut text = CocoaText::with_text("plain");
+        let text = text.as_any_mut().downcast_mut::<Text>().unwrap();
+        let control = text.inner().inner().inner().base.control;
+
+        text.set_markup("<b><a=https://example.com>link</a></b>");
+        unsafe {
+            assert!(!attribute(control, "NSLink").is_null());
+            let selectable: BOOL = msg_send![control, isSelectable];
+            assert_eq!(selectable, YES);
+        }
+
+        controls::HasLabel::set_label(text, "plain".into());
+        assert_eq!(controls::HasLabel::label(text), "plain");
+        unsafe {
+            assert!(attribute(control, "NSLink").is_null());
+            let selectable: BOOL = msg_send![control, isSelectable];
+            assert_eq!(selectable, NO);
+            let font = attribute(control, "NSFont");
+            let plain = text.inner().inner().inner().style.font.to_native();
+            assert_eq!(common::nsstring_to_string(msg_send![font, fontName]), common::nsstring_to_string(msg_send![plain, fontName]));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::application::ApplicationHandle;
+use crate::common::*;
+
+use core_foundation::base::TCFType;
+use core_foundation::date::CFDate;
+use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop, CFRunLoopTimer, CFRunLoopTimerContext, CFRunLoopTimerInvalidate, CFRunLoopTimerIsValid, CFRunLoopTimerRef};
+
+/// Timer callback, returning `false` stops a repeating timer. The return value of a one-shot timer is ignored.
+pub type OnTimer = Box<dyn FnMut(&mut dyn controls::Application) -> bool>;
+
+/// Cancellable handle of a timer started with `ApplicationExt::start_timer`.
+#[derive(Clone)]
+pub struct TimerHandle(CFRunLoopTimer);
+
+impl TimerHandle {
+    /// Stops the timer, the callback is not called anymore. Does nothing if the timer is already stopped.
+    pub fn cancel(&self) {
+        unsafe { CFRunLoopTimerInvalidate(self.0.as_concrete_TypeRef()) }
+    }
+    /// Whether the timer is still going to fire.
+    pub fn is_active(&self) -> bool {
+        unsafe { CFRunLoopTimerIsValid(self.0.as_concrete_TypeRef()) != 0 }
+    }
+}
+
+struct TimerState {
+    app: ApplicationHandle,
+    repeating: bool,
+    callback: OnTimer,
+}
+
+/// Schedules the timer on the main run loop in the common modes, so it keeps firing during menu tracking and window resizing.
+pub(crate) fn start(app: ApplicationHandle, interval_ms: u32, repeating: bool, callback: OnTimer) -> TimerHandle {
+    let state = Box::new(TimerState { app, repeating, callback });
+    let mut context = CFRunLoopTimerContext {
+        version: 0,
+        info: Box::into_raw(state) as *mut c_void,
+        retain: None,
+        release: Some(release_state),
+        copyDescription: None,
+    };
+    // a zero period would silently turn a repeating timer into a one-shot one
+    let interval = cmp::max(1, interval_ms) as f64 / 1000.0;
+    let timer = CFRunLoopTimer::new(CFDate::now().abs_time() + interval, if repeating { interval } else { 0.0 }, 0, 0, timer_fired, &mut context);
+    unsafe { CFRunLoop::get_main().add_timer(&timer, kCFRunLoopCommonModes) };
+    TimerHandle(timer)
+}
+
+extern "C" fn timer_fired(timer: CFRunLoopTimerRef, info: *mut c_void) {
+    let state = unsafe { &mut *(info as *mut TimerState) };
+    let keep = match unsafe { state.app.app_mut() } {
+        Some(app) => (state.callback.as_mut())(app),
+        None => false,
+    };
+    if !state.repeating || !keep {
+        unsafe { CFRunLoopTimerInvalidate(timer) };
+    }
+}
+
+extern "C" fn release_state(info: *const c_void) {
+    mem::drop(unsafe { Box::from_raw(info as *mut TimerState) });
+}
@@ -1,6 +1,14 @@
 use crate::common::{self, *};
 
-use cocoa::appkit::{NSSquareStatusItemLength, NSStatusBar};
+use cocoa::appkit::{NSSquareStatusItemLength, NSStatusBar, NSVariableStatusItemLength};
+use core_foundation::runloop::kCFRunLoopCommonModes;
+
+const NS_LEFT_MOUSE_UP_MASK: NSUInteger = 1 << 2;
+const NS_RIGHT_MOUSE_UP_MASK: NSUInteger = 1 << 4;
+const NS_IMAGE_LEFT: NSUInteger = 2;
+const NS_EVENT_MODIFIER_CONTROL: NSUInteger = 1 << 18;
+const NS_POPOVER_BEHAVIOR_TRANSIENT: NSInteger = 1;
+const NS_MIN_Y_EDGE: NSUInteger = 1;
 
 lazy_static! {
     static ref PLYGUI_MENU_ITEM_CLASS: common::RefClass = unsafe {
@@ -8,6 +16,17 @@ lazy_static! {
             decl.add_method(sel!(onTrayMenuItemSelect:), on_tray_menu_item_select as extern "C" fn(&mut Object, Sel, cocoa_id) -> BOOL);
         })
     };
+    static ref PLYGUI_TRAY_TARGET_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiTrayTarget", "NSObject", |decl| {
+            decl.add_method(sel!(onTrayClick:), on_tray_click as extern "C" fn(&mut Object, Sel, cocoa_id));
+            decl.add_method(sel!(onTrayAnimationTick:), on_tray_animation_tick as extern "C" fn(&mut Object, Sel, cocoa_id));
+        })
+    };
+    static ref PLYGUI_TRAY_POPOVER_CLASS: common::RefClass = unsafe {
+        register_window_class("PlyguiTrayPopover", "NSView", |decl| {
+            decl.add_method(sel!(isFlipped), is_flipped as extern "C" fn(&mut Object, Sel) -> BOOL);
+        })
+    };
 }
 
 #[repr(C)]
@@ -18,23 +37,247 @@ pub struct CocoaTray {
     menu: cocoa_id,
     menu_actions: HashMap<cocoa_id, callbacks::Action>,
     on_close: Option<callbacks::OnClose>,
+    target: cocoa_id,
+    template: bool,
+    on_left_click: Option<callbacks::Action>,
+    on_right_click: Option<callbacks::Action>,
+    animation: Vec<cocoa_id>,
+    animation_frame: usize,
+    animation_timer: cocoa_id,
+    popover: Option<Box<TrayPopover>>,
 }
 
 pub type Tray = AMember<ACloseable<ATray<CocoaTray>>>;
 
+/// Root of the control tree shown by `TrayExt::set_popover`, owns the content view of the popover.
+pub type TrayPopover = AMember<AContainer<ASingleContainer<CocoaTrayPopover>>>;
+
+#[repr(C)]
+pub struct CocoaTrayPopover {
+    popover: cocoa_id,
+    view: cocoa_id,
+    child: Option<Box<dyn controls::Control>>,
+}
+
 impl CocoaTray {
-    fn install_image(&mut self) {
+    fn status_image(&self, icon: &image::DynamicImage) -> cocoa_id {
         unsafe {
             let thickness: f64 = msg_send![NSStatusBar::systemStatusBar(nil), thickness];
-            let i = self.icon.resize(thickness as u32, thickness as u32, image::imageops::FilterType::Lanczos3);
+            let i = icon.resize(thickness as u32, thickness as u32, image::imageops::FilterType::Lanczos3);
             
         	let img = common::image_to_native(&i);
+        	let () = msg_send![img, setTemplate: if self.template { YES } else { NO }];
+        	img
+        }
+    }
+    fn install_image(&mut self) {
+        if self.tray.is_null() {
+            return;
+        }
+        unsafe {
+        	let img = self.status_image(&self.icon);
         	let btn: cocoa_id = msg_send![self.tray, button];
         	let () = msg_send![self.tray, setHighlightMode:YES];
-        	let () = msg_send![img, setTemplate:YES];
         	let () = msg_send![btn, setImage:img];
+        	let () = msg_send![img, release];
+        }
+    }
+    fn install_click_handlers(&mut self) {
+        unsafe {
+            let btn: cocoa_id = msg_send![self.tray, button];
+            if self.on_left_click.is_some() || self.on_right_click.is_some() || self.popover.is_some() {
+                // A status item with a menu never sends its action, so the menu is popped up manually from the click handler
+                let () = msg_send![self.tray, setMenu: nil];
+                let () = msg_send![btn, setTarget: self.target];
+                let () = msg_send![btn, setAction: sel!(onTrayClick:)];
+                let _: NSInteger = msg_send![btn, sendActionOn: NS_LEFT_MOUSE_UP_MASK | NS_RIGHT_MOUSE_UP_MASK];
+            } else {
+                let () = msg_send![btn, setTarget: nil];
+                let () = msg_send![btn, setAction: ptr::null::<c_void>()];
+                let () = msg_send![self.tray, setMenu: self.menu];
+            }
+        }
+    }
+    fn stop_animation(&mut self) {
+        unsafe {
+            if !self.animation_timer.is_null() {
+                let () = msg_send![self.animation_timer, invalidate];
+                self.animation_timer = nil;
+            }
+            for frame in self.animation.drain(..) {
+                let () = msg_send![frame, release];
+            }
+        }
+        self.animation_frame = 0;
+    }
+    fn show_popover(&mut self) {
+        if let Some(ref mut popover) = self.popover {
+            let popover = popover.inner_mut().inner_mut().inner_mut();
+            popover.fit();
+            unsafe {
+                let btn: cocoa_id = msg_send![self.tray, button];
+                let bounds: NSRect = msg_send![btn, bounds];
+                let app: cocoa_id = msg_send![class!(NSApplication), sharedApplication];
+                let () = msg_send![app, activateIgnoringOtherApps: YES];
+                let () = msg_send![popover.popover, showRelativeToRect:bounds ofView:btn preferredEdge:NS_MIN_Y_EDGE];
+            }
+        }
+    }
+    fn hide_popover(&mut self) {
+        if let Some(ref popover) = self.popover {
+            unsafe {
+                let () = msg_send![popover.inner().inner().inner().popover, performClose: nil];
+            }
+        }
+    }
+    fn is_popover_shown(&self) -> bool {
+        self.popover.as_ref().map(|popover| unsafe {
+            let shown: BOOL = msg_send![popover.inner().inner().inner().popover, isShown];
+            shown == YES
+        }).unwrap_or(false)
+    }
+    fn next_animation_frame(&mut self) {
+        if self.animation.is_empty() {
+            return;
+        }
+        self.animation_frame = (self.animation_frame + 1) % self.animation.len();
+        unsafe {
+        	let btn: cocoa_id = msg_send![self.tray, button];
+        	let () = msg_send![btn, setImage: self.animation[self.animation_frame]];
+        }
+    }
+}
+
+/// Cocoa-specific tray functionality not covered by `controls::Tray`.
+pub trait TrayExt {
+    /// Template icons (the default) are recolored by the system to match the menu bar, colored ones are shown as is.
+    fn set_icon_template(&mut self, template: bool);
+    fn is_icon_template(&self) -> bool;
+    fn set_tooltip(&mut self, tooltip: Option<&str>);
+    /// Text shown in the menu bar next to the icon.
+    fn set_title(&mut self, title: Option<&str>);
+    /// Once any click callback is set, the tray menu (if any) is shown only for clicks without a callback.
+    fn on_left_click(&mut self, callback: Option<callbacks::Action>);
+    fn on_right_click(&mut self, callback: Option<callbacks::Action>);
+    /// Cycles the icon through `frames` every `interval_ms` until `stop_animation` is called.
+    fn start_animation(&mut self, frames: &[image::DynamicImage], interval_ms: u32);
+    /// Stops the animation and restores the static icon.
+    fn stop_animation(&mut self);
+    /// Control tree shown in a popover below the icon on left click (unless `on_left_click` is set),
+    /// sized to what the content measures to. Returns the previous content.
+    fn set_popover(&mut self, content: Option<Box<dyn controls::Control>>) -> Option<Box<dyn controls::Control>>;
+    fn popover(&self) -> Option<&dyn controls::Control>;
+    fn popover_mut(&mut self) -> Option<&mut dyn controls::Control>;
+    /// Popover is dismissed automatically once the user interacts with anything outside of it.
+    fn set_popover_visible(&mut self, visible: bool);
+    fn is_popover_visible(&self) -> bool;
+}
+
+impl TrayExt for Tray {
+    fn set_icon_template(&mut self, template: bool) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        if tray.template != template {
+            tray.template = template;
+            if tray.animation.is_empty() {
+                tray.install_image();
+            }
+        }
+    }
+    fn is_icon_template(&self) -> bool {
+        self.inner().inner().inner().template
+    }
+    fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        unsafe {
+            let btn: cocoa_id = msg_send![tray.tray, button];
+            let tooltip = tooltip.map(|tooltip| NSString::alloc(nil).init_str(tooltip)).unwrap_or(nil);
+            let () = msg_send![btn, setToolTip: tooltip];
+            if !tooltip.is_null() {
+                let () = msg_send![tooltip, release];
+            }
+        }
+    }
+    fn set_title(&mut self, title: Option<&str>) {
+        controls::HasLabel::set_label(self, title.unwrap_or("").into());
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        unsafe {
+            if title.is_some() {
+                let btn: cocoa_id = msg_send![tray.tray, button];
+                let () = msg_send![tray.tray, setLength: NSVariableStatusItemLength];
+                let () = msg_send![btn, setImagePosition: NS_IMAGE_LEFT];
+            } else {
+                let () = msg_send![tray.tray, setLength: NSSquareStatusItemLength];
+            }
+        }
+    }
+    fn on_left_click(&mut self, callback: Option<callbacks::Action>) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        tray.on_left_click = callback;
+        tray.install_click_handlers();
+    }
+    fn on_right_click(&mut self, callback: Option<callbacks::Action>) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        tray.on_right_click = callback;
+        tray.install_click_handlers();
+    }
+    fn start_animation(&mut self, frames: &[image::DynamicImage], interval_ms: u32) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        tray.stop_animation();
+        if frames.is_empty() {
+            tray.install_image();
+            return;
+        }
+        tray.animation = frames.iter().map(|frame| tray.status_image(frame)).collect();
+        tray.animation_frame = tray.animation.len() - 1;
+        tray.next_animation_frame();
+        unsafe {
+            let interval = cmp::max(1, interval_ms) as f64 / 1000.0;
+            tray.animation_timer = msg_send![class!(NSTimer), timerWithTimeInterval:interval target:tray.target selector:sel!(onTrayAnimationTick:) userInfo:nil repeats:YES];
+            // common modes keep the icon moving while menus are open or windows are resized
+            let run_loop: cocoa_id = msg_send![class!(NSRunLoop), mainRunLoop];
+            let () = msg_send![run_loop, addTimer:tray.animation_timer forMode:kCFRunLoopCommonModes];
+        }
+    }
+    fn stop_animation(&mut self) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        tray.stop_animation();
+        tray.install_image();
+    }
+    fn set_popover(&mut self, content: Option<Box<dyn controls::Control>>) -> Option<Box<dyn controls::Control>> {
+        use plygui_api::controls::SingleContainer;
+
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        let old = match content {
+            Some(content) => tray.popover.get_or_insert_with(CocoaTrayPopover::new).set_child(Some(content)),
+            None => {
+                tray.hide_popover();
+                tray.popover.take().and_then(|mut popover| popover.set_child(None))
+            }
+        };
+        tray.install_click_handlers();
+        old
+    }
+    fn popover(&self) -> Option<&dyn controls::Control> {
+        use plygui_api::controls::SingleContainer;
+
+        self.inner().inner().inner().popover.as_ref().and_then(|popover| popover.child())
+    }
+    fn popover_mut(&mut self) -> Option<&mut dyn controls::Control> {
+        use plygui_api::controls::SingleContainer;
+
+        self.inner_mut().inner_mut().inner_mut().popover.as_mut().and_then(|popover| popover.child_mut())
+    }
+    fn set_popover_visible(&mut self, visible: bool) {
+        let tray = self.inner_mut().inner_mut().inner_mut();
+        if visible {
+            tray.show_popover();
+        } else {
+            tray.hide_popover();
         }
     }
+    fn is_popover_visible(&self) -> bool {
+        self.inner().inner().inner().is_popover_shown()
+    }
 }
 
 impl HasLabelInner for CocoaTray {
@@ -49,6 +292,7 @@ impl HasLabelInner for CocoaTray {
         unsafe {
             let label = NSString::alloc(cocoa::base::nil).init_str(&label);
             let () = msg_send![self.tray, setTitle: label];
+            let () = msg_send![label, release];
         }
     }
 }
@@ -62,6 +306,8 @@ impl CloseableInner for CocoaTray {
                 }
             }
         }
+        self.stop_animation();
+        self.hide_popover();
         unsafe {
             let status_bar: cocoa_id = NSStatusBar::systemStatusBar(ptr::null_mut());
             status_bar.removeStatusItem_(self.tray);
@@ -90,6 +336,10 @@ impl HasImageInner for CocoaTray {
 }
 impl<O: controls::Tray> NewTrayInner<O> for CocoaTray {
     fn with_uninit_params(u: &mut mem::MaybeUninit<O>, _: &mut dyn controls::Application, _: &str, icon: image::DynamicImage, menu: types::Menu) -> Self {
+        let target: cocoa_id = unsafe { msg_send![PLYGUI_TRAY_TARGET_CLASS.0, new] };
+        unsafe {
+            (&mut *target).set_ivar(IVAR, u as *mut _ as *mut c_void);
+        }
         CocoaTray {
             tray: ptr::null_mut(),
             this: u as *mut _ as *mut Tray,
@@ -97,6 +347,14 @@ impl<O: controls::Tray> NewTrayInner<O> for CocoaTray {
             menu_actions: if menu.is_some() { HashMap::new() } else { HashMap::with_capacity(0) },
             menu: nil,
             on_close: None,
+            target: target,
+            template: true,
+            on_left_click: None,
+            on_right_click: None,
+            animation: Vec::new(),
+            animation_frame: 0,
+            animation_timer: nil,
+            popover: None,
         }
     }
 }
@@ -143,9 +401,8 @@ impl TrayInner for CocoaTray {
             None => nil,
         };
 
-        unsafe {
-            let () = msg_send![t.inner_mut().inner_mut().inner_mut().tray, setMenu: menu];
-        }
+        t.inner_mut().inner_mut().inner_mut().menu = menu;
+        t.inner_mut().inner_mut().inner_mut().install_click_handlers();
         t
     }
 }
@@ -160,6 +417,212 @@ impl HasNativeIdInner for CocoaTray {
 
 impl MemberInner for CocoaTray {}
 
+impl Drop for CocoaTray {
+    fn drop(&mut self) {
+        self.stop_animation();
+        self.hide_popover();
+        unsafe {
+            let () = msg_send![self.target, release];
+        }
+    }
+}
+
+impl CocoaTrayPopover {
+    fn new() -> Box<TrayPopover> {
+        let mut b: Box<mem::MaybeUninit<TrayPopover>> = Box::new_uninit();
+        let inner = unsafe {
+            let popover: cocoa_id = msg_send![class!(NSPopover), new];
+            let () = msg_send![popover, setBehavior: NS_POPOVER_BEHAVIOR_TRANSIENT];
+            let () = msg_send![popover, setAnimates: YES];
+
+            let view: cocoa_id = msg_send![PLYGUI_TRAY_POPOVER_CLASS.0, alloc];
+            let view: cocoa_id = msg_send![view, initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1.0, 1.0))];
+            (&mut *view).set_ivar(IVAR, b.as_mut() as *mut _ as *mut c_void);
+
+            let controller: cocoa_id = msg_send![class!(NSViewController), new];
+            let () = msg_send![controller, setView: view];
+            let () = msg_send![popover, setContentViewController: controller];
+            let () = msg_send![controller, release];
+
+            CocoaTrayPopover { popover: popover, view: view, child: None }
+        };
+        let ab = AMember::with_inner(
+            AContainer::with_inner(
+                ASingleContainer::with_inner(inner),
+            )
+        );
+        unsafe {
+	        b.as_mut_ptr().write(ab);
+	        b.assume_init()
+        }
+    }
+    fn size(&self) -> (u16, u16) {
+        let frame: NSRect = unsafe { msg_send![self.view, frame] };
+        (frame.size.width as u16, frame.size.height as u16)
+    }
+    /// Resizes the popover to the measured size of the content, limited by the screen.
+    pub(crate) fn fit(&mut self) {
+        let (max_w, max_h) = unsafe {
+            let screen: cocoa_id = msg_send![class!(NSScreen), mainScreen];
+            let frame: NSRect = if screen.is_null() { NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1024.0, 768.0)) } else { msg_send![screen, visibleFrame] };
+            (frame.size.width as u16, frame.size.height as u16)
+        };
+        if let Some(ref mut child) = self.child {
+            let (w, h, _) = child.measure(max_w, max_h);
+            let size = NSSize::new(cmp::max(1, w) as f64, cmp::max(1, h) as f64);
+            unsafe {
+                let () = msg_send![self.popover, setContentSize: size];
+                let () = msg_send![self.view, setFrameSize: size];
+            }
+            child.draw(Some((0, 0)));
+        }
+    }
+}
+
+impl SingleContainerInner for CocoaTrayPopover {
+    fn set_child(&mut self, _: &mut MemberBase, mut child: Option<Box<dyn controls::Control>>) -> Option<Box<dyn controls::Control>> {
+        use plygui_api::controls::SingleContainer;
+
+        let mut old = self.child.take();
+        if let Some(old) = old.as_mut() {
+            let outer_self = unsafe { common::member_from_cocoa_id_mut::<TrayPopover>(self.view).unwrap() };
+            let outer_self = outer_self.as_single_container_mut().as_container_mut();
+            old.on_removed_from_container(outer_self);
+        }
+        if let Some(new) = child.as_mut() {
+            let (w, h) = self.size();
+            unsafe {
+                let () = msg_send![self.view, addSubview: new.native_id() as cocoa_id];
+            }
+            let outer_self = unsafe { common::member_from_cocoa_id_mut::<TrayPopover>(self.view).unwrap() };
+            let outer_self = outer_self.as_single_container_mut().as_container_mut();
+            new.on_added_to_container(outer_self, 0, 0, w, h);
+        }
+        self.child = child;
+        self.fit();
+        old
+    }
+    fn child(&self) -> Option<&dyn controls::Control> {
+        self.child.as_ref().map(|c| c.as_ref())
+    }
+    fn child_mut(&mut self) -> Option<&mut dyn controls::Control> {
+        if let Some(child) = self.child.as_mut() {
+            Some(child.as_mut())
+        } else {
+            None
+        }
+    }
+}
+
+impl ContainerInner for CocoaTrayPopover {
+    fn find_control_mut<'a>(&'a mut self, arg: types::FindBy<'a>) -> Option<&'a mut dyn controls::Control> {
+        if let Some(child) = self.child.as_mut() {
+            match arg {
+                types::FindBy::Id(id) => {
+                    if child.as_member_mut().id() == id {
+                        return Some(child.as_mut());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member_mut().tag() {
+                        if tag == mytag {
+                            return Some(child.as_mut());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container_mut() {
+                c.find_control_mut(arg)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    fn find_control<'a>(&'a self, arg: types::FindBy<'a>) -> Option<&'a dyn controls::Control> {
+        if let Some(child) = self.child.as_ref() {
+            match arg {
+                types::FindBy::Id(id) => {
+                    if child.as_member().id() == id {
+                        return Some(child.as_ref());
+                    }
+                }
+                types::FindBy::Tag(tag) => {
+                    if let Some(mytag) = child.as_member().tag() {
+                        if tag == mytag {
+                            return Some(child.as_ref());
+                        }
+                    }
+                }
+            }
+            if let Some(c) = child.is_container() {
+                c.find_control(arg)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl HasNativeIdInner for CocoaTrayPopover {
+    type Id = common::CocoaId;
+
+    fn native_id(&self) -> Self::Id {
+        self.view.into()
+    }
+}
+
+impl HasSizeInner for CocoaTrayPopover {
+    fn on_size_set(&mut self, _: &mut MemberBase, (width, height): (u16, u16)) -> bool {
+        let size = NSSize::new(cmp::max(1, width) as f64, cmp::max(1, height) as f64);
+        unsafe {
+            let () = msg_send![self.popover, setContentSize: size];
+            let () = msg_send![self.view, setFrameSize: size];
+        }
+        true
+    }
+}
+
+impl HasVisibilityInner for CocoaTrayPopover {
+    fn on_visibility_set(&mut self, _: &mut MemberBase, value: types::Visibility) -> bool {
+        unsafe {
+            let () = msg_send![self.view, setHidden: if types::Visibility::Visible == value { NO } else { YES }];
+        }
+        true
+    }
+}
+
+impl MemberInner for CocoaTrayPopover {}
+
+impl Drop for CocoaTrayPopover {
+    fn drop(&mut self) {
+        self.child = None;
+        unsafe {
+            let () = msg_send![self.popover, close];
+            let () = msg_send![self.popover, release];
+            crate::events::free_handlers(self.view);
+            let () = msg_send![self.view, release];
+        }
+    }
+}
+
+/// The topmost popover content view `id` lives in, if any. Popovers are shown in private windows with no member behind them,
+/// so this root stands in for the window when looking up the root of a control.
+pub(crate) unsafe fn popover_root(id: cocoa_id) -> Option<cocoa_id> {
+    let mut view = id;
+    while !view.is_null() {
+        let is_popover: BOOL = msg_send![view, isKindOfClass: PLYGUI_TRAY_POPOVER_CLASS.0];
+        if is_popover == YES {
+            return Some(view);
+        }
+        view = msg_send![view, superview];
+    }
+    None
+}
+
 extern "C" fn on_tray_menu_item_select(this: &mut Object, _: Sel, _: cocoa_id) -> BOOL {
     let key = this as cocoa_id;
     let tray = unsafe { common::member_from_cocoa_id_mut::<Tray>(this) }.unwrap();
@@ -171,3 +634,48 @@ extern "C" fn on_tray_menu_item_select(this: &mut Object, _: Sel, _: cocoa_id) -
     }
     YES
 }
+
+extern "C" fn on_tray_click(this: &mut Object, _: Sel, _: cocoa_id) {
+    let tray = unsafe { common::member_from_cocoa_id_mut::<Tray>(this) }.unwrap();
+    let tray2 = unsafe { common::member_from_cocoa_id_mut::<Tray>(this) }.unwrap();
+    let inner = tray.inner_mut().inner_mut().inner_mut();
+    let is_right = unsafe {
+        let app: cocoa_id = msg_send![class!(NSApplication), sharedApplication];
+        let event: cocoa_id = msg_send![app, currentEvent];
+        if event.is_null() {
+            false
+        } else {
+            let modifiers: NSUInteger = msg_send![event, modifierFlags];
+            let event_type: NSEventType = event.eventType();
+            event_type == NSEventType::NSRightMouseUp || event_type == NSEventType::NSRightMouseDown || modifiers & NS_EVENT_MODIFIER_CONTROL != 0
+        }
+    };
+    let callback = if is_right { inner.on_right_click.as_mut() } else { inner.on_left_click.as_mut() };
+    match callback {
+        Some(callback) => {
+            (callback.as_mut())(tray2);
+        }
+        None if !is_right && inner.popover.is_some() => {
+            if inner.is_popover_shown() {
+                inner.hide_popover();
+            } else {
+                inner.show_popover();
+            }
+        }
+        None => {
+            if !inner.menu.is_null() {
+                unsafe {
+                    let () = msg_send![inner.tray, popUpStatusItemMenu: inner.menu];
+                }
+            }
+        }
+    }
+}
+
+extern "C" fn on_tray_animation_tick(this: &mut Object, _: Sel, _: cocoa_id) {
+    let tray = unsafe { common::member_from_cocoa_id_mut::<Tray>(this) }.unwrap();
+    tray.inner_mut().inner_mut().inner_mut().next_animation_frame();
+}
+extern "C" fn is_flipped(_: &mut Object, _: Sel) -> BOOL {
+    YES
+}
@@ -3,10 +3,16 @@ use crate::common::{self, *};
 use cocoa::appkit::{NSBackingStoreBuffered, NSView, NSWindow, NSWindowStyleMask};
 use dispatch::Queue;
 
+use std::fmt;
+
 const BASE_CLASS: &str = "NSWindow";
+const NSWINDOW_TITLE_HIDDEN: NSInteger = 1;
+const NSWINDOW_ABOVE: NSInteger = 1;
 
 lazy_static! {
-    static ref WINDOW_CLASS: common::RefClass = unsafe { register_window_class("PlyguiWindow", BASE_CLASS, |_| {}) };
+    static ref WINDOW_CLASS: common::RefClass = unsafe { register_window_class("PlyguiWindow", BASE_CLASS, |decl| {
+            decl.add_method(sel!(canBecomeKeyWindow), can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL);
+        }) };
     static ref DELEGATE: common::RefClass = unsafe { register_delegate() };
     static ref PLYGUI_MENU_ITEM_CLASS: common::RefClass = unsafe {
         register_window_class("PlyguiWindowMenuItem", "NSMenuItem", |decl| {
@@ -30,6 +36,217 @@ pub struct CocoaWindow {
     on_close: Option<callbacks::OnClose>,
     skip_callbacks: bool,
     closed: bool,
+    owner: Option<(cocoa_id, WindowOwnership)>,
+}
+
+/// Window placement that survives application restarts.
+///
+/// Serializes into a compact `x,y,width,height,screen,zoomed` string, see `WindowExt::frame_state`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowFrameState {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub screen: u32,
+    pub zoomed: bool,
+}
+
+impl fmt::Display for WindowFrameState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{},{},{}", self.x, self.y, self.width, self.height, self.screen, if self.zoomed { 1 } else { 0 })
+    }
+}
+
+impl str::FromStr for WindowFrameState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.trim().split(',').map(|p| p.trim()).collect::<Vec<_>>();
+        if parts.len() != 6 {
+            return Err(());
+        }
+        let state = WindowFrameState {
+            x: parts[0].parse().map_err(|_| ())?,
+            y: parts[1].parse().map_err(|_| ())?,
+            width: parts[2].parse().map_err(|_| ())?,
+            height: parts[3].parse().map_err(|_| ())?,
+            screen: parts[4].parse().map_err(|_| ())?,
+            zoomed: match parts[5] {
+                "1" => true,
+                "0" => false,
+                _ => return Err(()),
+            },
+        };
+        if ![state.x, state.y, state.width, state.height].iter().all(|v| v.is_finite()) {
+            return Err(());
+        }
+        if state.width < 1.0 || state.height < 1.0 {
+            return Err(());
+        }
+        Ok(state)
+    }
+}
+
+/// Appearance of the window titlebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitlebarStyle {
+    Normal,
+    /// Titlebar is drawn over the content, which takes the full window size.
+    Transparent,
+    /// Same as `Transparent`, but the title text is hidden as well, only the window buttons remain.
+    Hidden,
+}
+
+impl Default for TitlebarStyle {
+    fn default() -> Self {
+        TitlebarStyle::Normal
+    }
+}
+
+/// How an owned window relates to its owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowOwnership {
+    /// Attached to the owner titlebar, blocks input to the owner until closed.
+    Sheet,
+    /// Blocks input to all other windows of the application until closed.
+    Modal,
+    /// Independent window that moves together with the owner and stays above it.
+    Child,
+}
+
+/// Construction options of a window, see `WindowExt::with_options`.
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(default)]
+pub struct WindowOptions {
+    /// Minimum size of the window content area.
+    #[builder(setter(strip_option))]
+    pub min_size: Option<(u16, u16)>,
+    /// Maximum size of the window content area.
+    #[builder(setter(strip_option))]
+    pub max_size: Option<(u16, u16)>,
+    /// Fixed content width to height ratio kept during resizing.
+    #[builder(setter(strip_option))]
+    pub aspect_ratio: Option<(u16, u16)>,
+    pub resizable: bool,
+    /// Window without titlebar and frame, e.g. a splash screen. Ignores `titlebar`.
+    pub borderless: bool,
+    pub titlebar: TitlebarStyle,
+    /// Placement to restore, see `WindowExt::frame_state`.
+    #[builder(setter(strip_option))]
+    pub frame_state: Option<WindowFrameState>,
+    /// Member (a window or any control inside it) owning the new window, and the kind of ownership.
+    #[builder(setter(strip_option))]
+    pub owner: Option<(ids::Id, WindowOwnership)>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        WindowOptions {
+            min_size: None,
+            max_size: None,
+            aspect_ratio: None,
+            resizable: true,
+            borderless: false,
+            titlebar: TitlebarStyle::Normal,
+            frame_state: None,
+            owner: None,
+        }
+    }
+}
+
+impl WindowOptions {
+    fn style_mask(&self) -> NSWindowStyleMask {
+        if self.borderless {
+            return NSWindowStyleMask::NSBorderlessWindowMask;
+        }
+        let mut mask = NSWindowStyleMask::NSClosableWindowMask | NSWindowStyleMask::NSMiniaturizableWindowMask | NSWindowStyleMask::NSTitledWindowMask;
+        if self.resizable {
+            mask |= NSWindowStyleMask::NSResizableWindowMask;
+        }
+        if self.titlebar != TitlebarStyle::Normal {
+            mask |= NSWindowStyleMask::NSFullSizeContentViewWindowMask;
+        }
+        mask
+    }
+    unsafe fn apply(&self, window: cocoa_id) {
+        if let Some((w, h)) = self.min_size {
+            let () = msg_send![window, setContentMinSize: NSSize::new(w as f64, h as f64)];
+        }
+        if let Some((w, h)) = self.max_size {
+            let () = msg_send![window, setContentMaxSize: NSSize::new(w as f64, h as f64)];
+        }
+        if let Some((w, h)) = self.aspect_ratio {
+            let () = msg_send![window, setContentAspectRatio: NSSize::new(w as f64, h as f64)];
+        }
+        if !self.borderless {
+            match self.titlebar {
+                TitlebarStyle::Normal => {}
+                TitlebarStyle::Transparent => {
+                    let () = msg_send![window, setTitlebarAppearsTransparent: YES];
+                }
+                TitlebarStyle::Hidden => {
+                    let () = msg_send![window, setTitlebarAppearsTransparent: YES];
+                    let () = msg_send![window, setTitleVisibility: NSWINDOW_TITLE_HIDDEN];
+                }
+            }
+        }
+    }
+}
+
+/// Cocoa-specific window functionality not covered by `controls::Window`.
+///
+/// Unlike `WindowInner::with_params`, whose result is handed to the application by the caller, the constructors here
+/// add the new window to the application roots themselves, same as `Application::new_window` does.
+pub trait WindowExt {
+    /// Creates a window placed according to `frame_state`, previously obtained from `frame_state()`.
+    /// Falls back to `fallback` size centered on the main screen, if the state cannot be parsed.
+    fn with_frame_state<'a, S: AsRef<str>>(app: &'a mut dyn controls::Application, title: S, frame_state: &str, fallback: types::WindowStartSize, menu: types::Menu) -> &'a mut dyn controls::Window
+    where
+        Self: Sized;
+    /// Creates a window with size constraints and style tweaks, see `WindowOptions`.
+    /// With `WindowOwnership::Modal` the modal session starts once the caller returns to the run loop, and lasts until the window is closed.
+    fn with_options<'a, S: AsRef<str>>(app: &'a mut dyn controls::Application, title: S, window_size: types::WindowStartSize, menu: types::Menu, options: WindowOptions) -> &'a mut dyn controls::Window
+    where
+        Self: Sized;
+    /// Current position, size, screen and zoom state as a serializable string.
+    fn frame_state(&self) -> String;
+    /// Restores the window placement, returns `false` if the state cannot be parsed.
+    fn set_frame_state(&mut self, frame_state: &str) -> bool;
+    /// Kind of ownership, if the window was created with an owner.
+    fn ownership(&self) -> Option<WindowOwnership>;
+}
+
+impl WindowExt for Window {
+    fn with_frame_state<'a, S: AsRef<str>>(app: &'a mut dyn controls::Application, title: S, frame_state: &str, fallback: types::WindowStartSize, menu: types::Menu) -> &'a mut dyn controls::Window {
+        let options = WindowOptions {
+            frame_state: frame_state.parse::<WindowFrameState>().ok(),
+            ..Default::default()
+        };
+        let window = CocoaWindow::with_options(app, title.as_ref(), fallback, menu, &options);
+        add_root(app, window)
+    }
+    fn with_options<'a, S: AsRef<str>>(app: &'a mut dyn controls::Application, title: S, window_size: types::WindowStartSize, menu: types::Menu, options: WindowOptions) -> &'a mut dyn controls::Window {
+        let window = CocoaWindow::with_options(app, title.as_ref(), window_size, menu, &options);
+        let window = add_root(app, window);
+        unsafe { window.as_any_mut().downcast_mut::<Window>().unwrap().inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().attach_to_owner() };
+        window
+    }
+    fn frame_state(&self) -> String {
+        self.inner().inner().inner().inner().inner().frame_state().to_string()
+    }
+    fn set_frame_state(&mut self, frame_state: &str) -> bool {
+        match frame_state.parse::<WindowFrameState>() {
+            Ok(frame_state) => {
+                unsafe { apply_frame_state(self.inner().inner().inner().inner().inner().window, &frame_state) };
+                true
+            }
+            Err(_) => false,
+        }
+    }
+    fn ownership(&self) -> Option<WindowOwnership> {
+        self.inner().inner().inner().inner().inner().owner.map(|(_, ownership)| ownership)
+    }
 }
 
 impl CocoaWindow {
@@ -51,7 +268,19 @@ impl CocoaWindow {
 impl CloseableInner for CocoaWindow {
     fn close(&mut self, skip_callbacks: bool) -> bool {
         self.skip_callbacks = skip_callbacks;
-        let () = unsafe { msg_send![self.window, performClose:self.window] };
+        unsafe {
+            let style: NSUInteger = msg_send![self.window, styleMask];
+            if style & NSWindowStyleMask::NSClosableWindowMask.bits() != 0 {
+                let () = msg_send![self.window, performClose:self.window];
+            } else {
+                // performClose: only beeps at windows without the close button, e.g. borderless ones
+                let delegate: cocoa_id = msg_send![self.window, delegate];
+                let should_close: BOOL = msg_send![delegate, windowShouldClose: self.window];
+                if should_close == YES {
+                    let () = msg_send![self.window, close];
+                }
+            }
+        }
         let visible: BOOL = unsafe { msg_send![self.window, isVisible] };
         visible == NO
     }
@@ -66,8 +295,8 @@ impl CloseableInner for CocoaWindow {
     }
 }
 
-impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
-    fn with_uninit_params(u: &mut mem::MaybeUninit<O>, _: &mut dyn controls::Application, title: &str, window_size: types::WindowStartSize, menu: types::Menu) -> Self {
+impl CocoaWindow {
+    fn with_uninit_options<O: controls::Window>(u: &mut mem::MaybeUninit<O>, title: &str, window_size: types::WindowStartSize, menu: types::Menu, options: &WindowOptions, owner: Option<(cocoa_id, WindowOwnership)>) -> Self {
         let selfptr = u as *mut _ as *mut c_void;
    		let rect: NSRect = match window_size {
             types::WindowStartSize::Exact(width, height) => NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(width as f64, height as f64)),
@@ -81,14 +310,21 @@ impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
             let window: cocoa_id = msg_send![WINDOW_CLASS.0, alloc];
             let window = window.initWithContentRect_styleMask_backing_defer_(
                 rect,
-                NSWindowStyleMask::NSClosableWindowMask | NSWindowStyleMask::NSResizableWindowMask | NSWindowStyleMask::NSMiniaturizableWindowMask | NSWindowStyleMask::NSTitledWindowMask,
+                options.style_mask(),
                 NSBackingStoreBuffered,
                 NO,
             );
+            options.apply(window);
             let () = msg_send![window ,cascadeTopLeftFromPoint: NSPoint::new(20., 20.)];
             window.center();
+            if let Some(ref frame_state) = options.frame_state {
+                apply_frame_state(window, frame_state);
+            }
             let () = msg_send![window, setTitle: title];
-            let () = msg_send![window, makeKeyAndOrderFront: nil];
+            let is_sheet = owner.map(|(_, ownership)| ownership == WindowOwnership::Sheet).unwrap_or(false);
+            if !is_sheet {
+                let () = msg_send![window, makeKeyAndOrderFront: nil];
+            }
             let current_app = cocoa::appkit::NSRunningApplication::currentApplication(nil);
             let () = msg_send![current_app, activateWithOptions: cocoa::appkit::NSApplicationActivateIgnoringOtherApps];
     
@@ -99,8 +335,10 @@ impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
             (&mut *delegate).set_ivar(common::IVAR, selfptr);
             (&mut *window).set_ivar(common::IVAR, selfptr);
             let () = msg_send![window, setDelegate: delegate];
-            let () = msg_send![window, makeKeyAndOrderFront: nil];
-    
+            if !is_sheet {
+                let () = msg_send![window, makeKeyAndOrderFront: nil];
+            }
+
             CocoaWindow {
                 window: window,
                 container: view,
@@ -111,6 +349,7 @@ impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
                 on_close: None,
                 skip_callbacks: false,
                 closed: false,
+                owner: owner,
             }
         };
 		w.menu = match menu {
@@ -134,17 +373,20 @@ impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
         };
 		w
     }
-}
-impl WindowInner for CocoaWindow {
-    fn with_params<S: AsRef<str>>(app: &mut dyn controls::Application, title: S, window_size: types::WindowStartSize, menu: types::Menu) -> Box<dyn controls::Window> {
+    fn with_options(app: &mut dyn controls::Application, title: &str, window_size: types::WindowStartSize, menu: types::Menu, options: &WindowOptions) -> Box<Window> {
         let app = app.as_any_mut().downcast_mut::<crate::application::Application>().unwrap();
+        let owner = options.owner.and_then(|(id, ownership)| {
+            controls::Application::find_member(app, types::FindBy::Id(id))
+                .and_then(|member| unsafe { common::parent_cocoa_id(member.native_id() as cocoa_id, true) })
+                .map(|owner| (owner, ownership))
+        });
         let mut b: Box<mem::MaybeUninit<Window>> = Box::new_uninit();
         let ab = AMember::with_inner(
             AContainer::with_inner(
                 ASingleContainer::with_inner(
                     ACloseable::with_inner(
                         AWindow::with_inner(
-                            <Self as NewWindowInner<Window>>::with_uninit_params(b.as_mut(), app, title.as_ref(), window_size, menu),
+                            Self::with_uninit_options(b.as_mut(), title, window_size, menu, options, owner),
     	                ),
                         app
                     )
@@ -156,6 +398,98 @@ impl WindowInner for CocoaWindow {
 	        b.assume_init()
         }
     }
+    unsafe fn attach_to_owner(&mut self) {
+        if let Some((owner, ownership)) = self.owner {
+            match ownership {
+                WindowOwnership::Sheet => {
+                    let () = msg_send![owner, beginSheet:self.window completionHandler:nil];
+                }
+                WindowOwnership::Modal => {
+                    // `runModalForWindow:` only returns when the window closes, which frees it, so the session
+                    // must not run while the constructor still holds the window
+                    let () = msg_send![self.window, retain];
+                    let window = self.window as usize;
+                    Queue::main().exec_async(move || {
+                        let window = window as cocoa_id;
+                        let visible: BOOL = msg_send![window, isVisible];
+                        if visible == YES {
+                            let app: cocoa_id = msg_send![class!(NSApplication), sharedApplication];
+                            let _: NSInteger = msg_send![app, runModalForWindow: window];
+                        }
+                        let () = msg_send![window, release];
+                    });
+                }
+                WindowOwnership::Child => {
+                    let () = msg_send![owner, addChildWindow:self.window ordered:NSWINDOW_ABOVE];
+                }
+            }
+        }
+    }
+    unsafe fn detach_from_owner(&mut self) {
+        if let Some((owner, ownership)) = self.owner.take() {
+            match ownership {
+                WindowOwnership::Sheet => {
+                    let () = msg_send![owner, endSheet: self.window];
+                }
+                WindowOwnership::Modal => {
+                    let app: cocoa_id = msg_send![class!(NSApplication), sharedApplication];
+                    let modal: cocoa_id = msg_send![app, modalWindow];
+                    if modal == self.window {
+                        let () = msg_send![app, stopModal];
+                    }
+                }
+                WindowOwnership::Child => {
+                    let () = msg_send![owner, removeChildWindow: self.window];
+                }
+            }
+        }
+    }
+    /// Ends the ownership of this window and of the windows it owns, finishing their sheets and modal sessions.
+    pub(crate) unsafe fn detach_chain(&mut self, app: &crate::application::Application) {
+        for owned in CocoaWindow::owned_by(app, self.window) {
+            if let Some(owned) = common::member_from_cocoa_id_mut::<Window>(owned) {
+                owned.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().detach_chain(app);
+            }
+        }
+        self.detach_from_owner();
+    }
+    /// Native windows owned by `window` among the application roots.
+    pub(crate) fn owned_by(app: &crate::application::Application, window: cocoa_id) -> Vec<cocoa_id> {
+        app.base
+            .windows
+            .iter()
+            .filter_map(|w| w.as_any().downcast_ref::<Window>())
+            .map(|w| w.inner().inner().inner().inner().inner())
+            .filter(|w| w.owner.map(|(owner, _)| owner == window).unwrap_or(false))
+            .map(|w| w.window)
+            .collect()
+    }
+    fn frame_state(&self) -> WindowFrameState {
+        unsafe {
+            let frame: NSRect = msg_send![self.window, frame];
+            let screen: cocoa_id = msg_send![self.window, screen];
+            let zoomed: BOOL = msg_send![self.window, isZoomed];
+            WindowFrameState {
+                x: frame.origin.x,
+                y: frame.origin.y,
+                width: frame.size.width,
+                height: frame.size.height,
+                screen: screen_number(screen),
+                zoomed: zoomed == YES,
+            }
+        }
+    }
+}
+
+impl<O: controls::Window> NewWindowInner<O> for CocoaWindow {
+    fn with_uninit_params(u: &mut mem::MaybeUninit<O>, _: &mut dyn controls::Application, title: &str, window_size: types::WindowStartSize, menu: types::Menu) -> Self {
+        Self::with_uninit_options(u, title, window_size, menu, &WindowOptions::default(), None)
+    }
+}
+impl WindowInner for CocoaWindow {
+    fn with_params<S: AsRef<str>>(app: &mut dyn controls::Application, title: S, window_size: types::WindowStartSize, menu: types::Menu) -> Box<dyn controls::Window> {
+        Self::with_options(app, title.as_ref(), window_size, menu, &WindowOptions::default())
+    }
     fn size(&self) -> (u16, u16) {
         self.size_inner()
     }
@@ -204,6 +538,7 @@ impl SingleContainerInner for CocoaWindow {
             new.draw(Some((0, 0)));
         }
         self.child = child;
+        unsafe { crate::focus::update_key_view_loop(common::member_from_cocoa_id_mut::<Window>(self.window).unwrap()) };
         old
     }
     fn child(&self) -> Option<&dyn controls::Control> {
@@ -275,11 +610,73 @@ impl MemberInner for CocoaWindow {}
 impl Drop for CocoaWindow {
     fn drop(&mut self) {
         unsafe {
+            crate::events::free_handlers(self.window);
             self.container.removeFromSuperview();
         }
     }
 }
 
+/// Registers a window made by `WindowExt` among the application roots.
+fn add_root<'a>(app: &'a mut dyn controls::Application, window: Box<Window>) -> &'a mut dyn controls::Window {
+    let app = app.as_any_mut().downcast_mut::<crate::application::Application>().unwrap();
+    app.inner_mut().add_root(window).as_any_mut().downcast_mut::<Window>().unwrap()
+}
+
+unsafe fn screen_number(screen: cocoa_id) -> u32 {
+    if screen.is_null() {
+        return 0;
+    }
+    let description: cocoa_id = msg_send![screen, deviceDescription];
+    let key = NSString::alloc(nil).init_str("NSScreenNumber");
+    let number: cocoa_id = msg_send![description, objectForKey: key];
+    let () = msg_send![key, release];
+    if number.is_null() {
+        0
+    } else {
+        msg_send![number, unsignedIntValue]
+    }
+}
+
+unsafe fn screen_by_number(number: u32) -> Option<cocoa_id> {
+    let screens: cocoa_id = msg_send![class!(NSScreen), screens];
+    let count: NSUInteger = msg_send![screens, count];
+    (0..count)
+        .map(|i| {
+            let screen: cocoa_id = msg_send![screens, objectAtIndex: i];
+            screen
+        })
+        .find(|screen| screen_number(*screen) == number)
+}
+
+unsafe fn apply_frame_state(window: cocoa_id, frame_state: &WindowFrameState) {
+    // The screen the window was saved on may be gone (unplugged monitor, changed arrangement),
+    // so the frame is always fitted into the visible area of an existing screen.
+    let (screen, found) = match screen_by_number(frame_state.screen) {
+        Some(screen) => (screen, true),
+        None => (msg_send![class!(NSScreen), mainScreen], false),
+    };
+    if screen.is_null() {
+        return;
+    }
+    let visible: NSRect = msg_send![screen, visibleFrame];
+    let mut frame = NSRect::new(NSPoint::new(frame_state.x, frame_state.y), NSSize::new(frame_state.width, frame_state.height));
+    frame.size.width = frame.size.width.min(visible.size.width);
+    frame.size.height = frame.size.height.min(visible.size.height);
+    if found {
+        frame.origin.x = frame.origin.x.max(visible.origin.x).min(visible.origin.x + visible.size.width - frame.size.width);
+        frame.origin.y = frame.origin.y.max(visible.origin.y).min(visible.origin.y + visible.size.height - frame.size.height);
+    } else {
+        frame.origin.x = visible.origin.x + (visible.size.width - frame.size.width) / 2.0;
+        frame.origin.y = visible.origin.y + (visible.size.height - frame.size.height) / 2.0;
+    }
+    let () = msg_send![window, setFrame:frame display:YES];
+
+    let zoomed: BOOL = msg_send![window, isZoomed];
+    if frame_state.zoomed != (zoomed == YES) {
+        let () = msg_send![window, zoom: nil];
+    }
+}
+
 unsafe fn register_delegate() -> common::RefClass {
     let superclass = Class::get("NSObject").unwrap();
     let mut decl = ClassDecl::new("PlyguiWindowDelegate", superclass).unwrap();
@@ -301,6 +698,11 @@ fn window_redraw<O: controls::Window>(window: &mut Window) {
     window.call_on_size::<O>(size.0, size.1);
 }
 
+extern "C" fn can_become_key_window(_: &Object, _: Sel) -> BOOL {
+    // Borderless windows refuse to become key by default, which breaks keyboard input in them.
+    YES
+}
+
 extern "C" fn window_did_become_key(this: &mut Object, _: Sel, _: cocoa_id) {
     let window = unsafe { common::member_from_cocoa_id_mut::<Window>(this) }.unwrap();
     let menu = window.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().menu;
@@ -330,6 +732,18 @@ extern "C" fn window_should_close(_: &mut Object, _: Sel, param: cocoa_id) -> BO
             }
         }
     }
+    
+    // Owned windows go first, any of them may veto closing of the whole chain
+    let owned = CocoaWindow::owned_by(window.inner_mut().inner_mut().inner_mut().application_impl_mut::<crate::application::Application>(), param);
+    let skip_callbacks = window.inner().inner().inner().inner().inner().skip_callbacks;
+    for owned in owned {
+        let owned = unsafe { common::member_from_cocoa_id_mut::<Window>(owned) }.unwrap();
+        if !owned.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().close(skip_callbacks) {
+            return NO;
+        }
+    }
+    unsafe { window.inner_mut().inner_mut().inner_mut().inner_mut().inner_mut().detach_from_owner() };
+    
     let id = window.id();
     let cid = param as usize;
     Queue::main().exec_async(move || {